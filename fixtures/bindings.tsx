@@ -0,0 +1,19 @@
+import React from "react";
+
+const base = "flex items-center text-sm";
+const styles = {
+  primary: "bg-blue-500 text-white",
+  secondary: "bg-white text-gray-800",
+};
+const title = "visible";
+let mutable = "uppercase";
+
+export function Card({ extra, kind }) {
+  return (
+    <div className={cn(base, extra)}>
+      <h1 className={styles[kind]}>{title}</h1>
+      <span className={title}>×</span>
+      <span className={mutable}>×</span>
+    </div>
+  );
+}
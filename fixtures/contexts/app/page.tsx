@@ -0,0 +1,3 @@
+import { buttonStyles } from "../ui/styles";
+
+export const Page = () => <button className={buttonStyles} />;
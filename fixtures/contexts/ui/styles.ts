@@ -0,0 +1 @@
+export const buttonStyles = "px-2 py-1";
@@ -0,0 +1,17 @@
+const external = /* cnat */ "px-2 py-1";
+const label = "px-4 py-2";
+
+export function Card() {
+  return (
+    <div className="bg-white">
+      {/* cnat-ignore-next-line matched by third-party css */}
+      <span className="uppercase" />
+      <span className="uppercase" />
+      {/* cnat-disable */}
+      <p className="w-10 text-white" />
+      <p className={`w-10 ${label}`} />
+      {/* cnat-enable */}
+      <p className="w-10 text-white" />
+    </div>
+  );
+}
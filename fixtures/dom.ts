@@ -0,0 +1,13 @@
+const el = document.getElementById("menu")!;
+
+el.className = "uppercase px-2";
+document.body.className += " visible";
+el.dataset.state = "visible";
+
+el.classList.add("sr-only", "py-1");
+el.classList.toggle("uppercase", true);
+document.querySelector(".menu")?.classList.remove("visible");
+getButton().classList.replace("px-2", "px-4");
+
+const items = new Set();
+items.add("visible");
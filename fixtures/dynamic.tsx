@@ -0,0 +1,16 @@
+import React from "react";
+
+export function Badge({ color, variant, size }) {
+  return (
+    <div className={`text-${color}-600 ${size} flex`}>
+      <span className={"bg-" + variant + " py-1"}>×</span>
+      <span className={"text-sm " + size}>×</span>
+      <span className={cn(`rounded-${size}`, "p-2")}>×</span>
+      <span className={"py-1\tpx-" + size}>×</span>
+      <span className={"m-" + cn("bg-" + variant)}>×</span>
+      <span className={`py-1\tpx-${size}`}>×</span>
+    </div>
+  );
+}
+
+const label = `label-${color}`;
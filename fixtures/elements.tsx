@@ -0,0 +1,14 @@
+import React from "react";
+import { Dialog } from "@headlessui/react";
+
+export function Modal({ open }) {
+  return (
+    <Dialog open={open} className="px-4 py-2">
+      <Dialog.Panel panelClassName="bg-white" title="visible">
+        <Icon color="text-white" size="uppercase" />
+        <Badge color="uppercase" variant={open ? "sr-only" : "visible"} />
+        <Tooltip text="visible" side={"uppercase"} />
+      </Dialog.Panel>
+    </Dialog>
+  );
+}
@@ -0,0 +1,11 @@
+const label = 'it\'s px-2 py-1';
+const spaced = "flex\u0020px-2\x20py-1";
+const continued = "px-4 \
+py-2";
+const selected = document.querySelector(".md\\:px-2 .uppercase");
+
+export const Badge = () => (
+  <span className="px-2 \ py-1">
+    <b className={"text-white \"quoted\" py-1"} />
+  </span>
+);
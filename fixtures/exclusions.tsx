@@ -0,0 +1,9 @@
+import React, { createElement } from "react";
+
+export function Banner({ t }) {
+  return (
+    <div className={cn(t("uppercase"), "px-2 py-1")}>
+      {createElement("span", { className: "visible", testId: "visible" })}
+    </div>
+  );
+}
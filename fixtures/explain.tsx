@@ -0,0 +1,9 @@
+const title = "px-4 py-2";
+
+export function Card({ size }: { size: string }) {
+  return (
+    <div className={cn("px-2 tw-py-1 bg-red-999", t("text-white"))}>
+      <span className={`w-10 ${size}`} title="uppercase" />
+    </div>
+  );
+}
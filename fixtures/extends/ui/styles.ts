@@ -0,0 +1 @@
+export const card = "px-2 py-1";
@@ -0,0 +1,2 @@
+// @generated by graphql-codegen, do not edit
+export const Badge = () => <span className="px-2 py-1" />;
@@ -0,0 +1 @@
+React.createElement("div",{className:"px-2 py-1"});
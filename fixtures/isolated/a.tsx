@@ -0,0 +1,5 @@
+import { extra } from "./extra";
+
+const base = ["px-2", extra];
+
+export const Card = () => <div className={base} />;
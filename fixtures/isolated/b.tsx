@@ -0,0 +1 @@
+const extra = "py-1";
@@ -0,0 +1,16 @@
+import React from "react";
+import clsx from "clsx";
+
+const props = {
+  "className": "px-2 py-1",
+  ["className"]: "uppercase",
+  [`className`]: "visible",
+};
+
+export function Alert({ isError, hidden }) {
+  return (
+    <div className={clsx("px-4", { "bg-white": !isError, ["sr-only"]: hidden })}>
+      <span classList={{ "text-sm": true, "text-white": isError }} {...props} />
+    </div>
+  );
+}
@@ -0,0 +1,12 @@
+import React from "react";
+import accent, { buttonStyles, buttonLabel, tooltip } from "./styles";
+
+export function Button({ kind, children }) {
+  console.log(tooltip);
+  return (
+    <button className={buttonStyles[kind]}>
+      <span className={buttonLabel}>{children}</span>
+      <span className={cn(accent, tooltip)}>?</span>
+    </button>
+  );
+}
@@ -0,0 +1,12 @@
+export const buttonStyles = {
+  primary: "bg-blue-500 text-white px-4 py-2",
+  secondary: "bg-white text-gray-800 px-4 py-2",
+};
+
+const label = "uppercase";
+export { label as buttonLabel };
+
+export const tooltip = "sr-only";
+
+const accent = "text-white";
+export default accent;
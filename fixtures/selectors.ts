@@ -0,0 +1,6 @@
+const card = document.querySelector(".card .px-4 > .text-white");
+const items = document.querySelectorAll("li.uppercase:not(.visible), .px-2");
+const hidden = page.locator(".sr-only");
+const menu = card.closest("[data-menu] .py-1");
+const broken = page.locator("text=px-4");
+document.querySelector(".px-4").classList.add("px-4");
@@ -0,0 +1,3 @@
+import { label } from "./styles";
+
+export const Label = () => <span className={label} />;
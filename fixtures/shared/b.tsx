@@ -0,0 +1,3 @@
+import { label } from "./styles";
+
+export const Input = () => <input placeholder={label} />;
@@ -0,0 +1 @@
+export const label = "px-2 py-1";
@@ -0,0 +1,18 @@
+import { Icon } from "./icon";
+
+const buttonClasses = "px-4 py-2 bg-blue-500";
+
+export function Button({ open }: { open: boolean }) {
+  if (open) {
+    document.body.classList.add("bg-white", "text-gray-800");
+  }
+
+  return (
+    <button className="px-2 py-1 text-white">
+      <Icon color="text-white" label="Open the menu" />
+      <Icon color="hover:bg-white border-gray-400" />
+      <Popover classes={{ paper: "px-2 w-10", root: "uppercase" }} />
+      {styled("span", "sr-only")}
+    </button>
+  );
+}
@@ -0,0 +1,14 @@
+export type Gap = "px-2" | "px-4" | { value: "py-1" };
+export type Size = "uppercase" | "visible";
+
+export enum Tone {
+  Danger = "text-white",
+  Muted = "text-gray-800 uppercase",
+  "sr-only" = "sr-only",
+}
+
+export enum Mode {
+  Visible = "visible",
+}
+
+export const gap: Gap = "px-2";
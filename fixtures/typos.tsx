@@ -0,0 +1,9 @@
+import React from "react";
+
+export default function Typos() {
+  return (
+    <div className="text-grey-800 bg-blue-50O visible group">
+      <span className="tw-text-sm peer MuiButton-startIcon">×</span>
+    </div>
+  );
+}
@@ -0,0 +1,21 @@
+import { cva } from "class-variance-authority";
+
+export const badge = cva("px-2 py-1", {
+  variants: {
+    display: {
+      visible: "visible",
+      hidden: ["sr-only"],
+    },
+    casing: {
+      uppercase: "uppercase",
+      none: "",
+    },
+  },
+  compoundVariants: [
+    { display: "visible", casing: "uppercase", class: "text-sm" },
+  ],
+  defaultVariants: {
+    display: "visible",
+    casing: "uppercase",
+  },
+});
@@ -0,0 +1,15 @@
+export const Card = ({ size }) => (
+  <div
+    className="
+      px-2
+      py-1	uppercase
+    "
+  >
+    <span className={"w-10\tpx-4\npy-2"} />
+    <span className={"\n\t\tsr-only\r\n\t\tvisible"} />
+    <span className={`
+      px-2
+      py-1`} />
+    <span className={`w-10\tpx-${size} py-2`} />
+  </div>
+);
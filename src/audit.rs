@@ -0,0 +1,282 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::collect::ClassNamesCollector;
+use crate::report::Report;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum AuditFormat {
+    Text,
+    Json,
+    Csv,
+}
+
+#[derive(Serialize)]
+struct ClassUsage<'a> {
+    class: &'a str,
+    uses: usize,
+    files: BTreeSet<&'a Path>,
+    scopes: BTreeMap<&'a str, usize>,
+}
+
+#[derive(Serialize)]
+struct DirectoryUsage<'a> {
+    directory: &'a Path,
+    uses: usize,
+    classes: BTreeMap<&'a str, usize>,
+}
+
+/// Usage statistics of the classes found in scope, built from a dry run's report.
+pub struct Audit {
+    /// (file, class, scope) -> uses
+    usages: BTreeMap<(PathBuf, String, String), usize>,
+}
+
+impl Audit {
+    pub fn new(report: &Report) -> Self {
+        let mut usages = BTreeMap::new();
+
+        for r in report.replacements() {
+            let scope = format!("{}:{}", r.scope.variant, r.scope.value);
+            for class in &r.classes {
+                *usages
+                    .entry((r.file.clone(), class.clone(), scope.clone()))
+                    .or_default() += 1;
+            }
+        }
+
+        Self { usages }
+    }
+
+    /// Most used classes first.
+    fn classes(&self) -> Vec<ClassUsage<'_>> {
+        let mut classes: BTreeMap<&str, ClassUsage> = BTreeMap::new();
+
+        for ((file, class, scope), uses) in &self.usages {
+            let usage = classes.entry(class).or_insert_with(|| ClassUsage {
+                class,
+                uses: 0,
+                files: BTreeSet::new(),
+                scopes: BTreeMap::new(),
+            });
+            usage.uses += uses;
+            usage.files.insert(file);
+            *usage.scopes.entry(scope).or_default() += uses;
+        }
+
+        let mut classes: Vec<_> = classes.into_values().collect();
+        classes.sort_by(|a, b| b.uses.cmp(&a.uses).then(a.class.cmp(b.class)));
+        classes
+    }
+
+    fn directories(&self) -> Vec<DirectoryUsage<'_>> {
+        let mut directories: BTreeMap<&Path, DirectoryUsage> = BTreeMap::new();
+
+        for ((file, class, _), uses) in &self.usages {
+            let directory = file.parent().unwrap_or(Path::new(""));
+            let usage = directories
+                .entry(directory)
+                .or_insert_with(|| DirectoryUsage {
+                    directory,
+                    uses: 0,
+                    classes: BTreeMap::new(),
+                });
+            usage.uses += uses;
+            *usage.classes.entry(class).or_default() += uses;
+        }
+
+        directories.into_values().collect()
+    }
+
+    pub fn write(&self, format: AuditFormat, mut out: impl Write) -> anyhow::Result<()> {
+        match format {
+            AuditFormat::Text => {
+                let classes = self.classes();
+                let width = classes.iter().map(|c| c.class.len()).max().unwrap_or(0);
+                let width = width.max("CLASS".len());
+
+                writeln!(
+                    out,
+                    "{:width$}  {:>6}  {:>6}  SCOPES",
+                    "CLASS", "USES", "FILES"
+                )?;
+                for c in &classes {
+                    let scopes: Vec<_> = c
+                        .scopes
+                        .iter()
+                        .map(|(scope, uses)| format!("{scope} ({uses})"))
+                        .collect();
+                    writeln!(
+                        out,
+                        "{:width$}  {:>6}  {:>6}  {}",
+                        c.class,
+                        c.uses,
+                        c.files.len(),
+                        scopes.join(", ")
+                    )?;
+                }
+
+                writeln!(out)?;
+
+                let directories = self.directories();
+                let width = directories
+                    .iter()
+                    .map(|d| d.directory.to_string_lossy().len())
+                    .max()
+                    .unwrap_or(0);
+                let width = width.max("DIRECTORY".len());
+
+                writeln!(
+                    out,
+                    "{:width$}  {:>6}  {:>7}",
+                    "DIRECTORY", "USES", "CLASSES"
+                )?;
+                for d in &directories {
+                    writeln!(
+                        out,
+                        "{:width$}  {:>6}  {:>7}",
+                        d.directory.display(),
+                        d.uses,
+                        d.classes.len()
+                    )?;
+                }
+            }
+            AuditFormat::Json => {
+                #[derive(Serialize)]
+                struct JsonAudit<'a> {
+                    classes: Vec<ClassUsage<'a>>,
+                    directories: Vec<DirectoryUsage<'a>>,
+                }
+
+                serde_json::to_writer_pretty(
+                    &mut out,
+                    &JsonAudit {
+                        classes: self.classes(),
+                        directories: self.directories(),
+                    },
+                )?;
+                writeln!(out)?;
+            }
+            AuditFormat::Csv => {
+                writeln!(out, "directory,file,class,scope,uses")?;
+                for ((file, class, scope), uses) in &self.usages {
+                    let directory = file.parent().unwrap_or(Path::new(""));
+                    writeln!(
+                        out,
+                        "{},{},{},{},{}",
+                        csv_field(&directory.to_string_lossy()),
+                        csv_field(&file.to_string_lossy()),
+                        csv_field(class),
+                        csv_field(scope),
+                        uses
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[derive(Serialize)]
+struct UnusedClass<'c> {
+    class: &'c str,
+    rule: &'c str,
+    line: usize,
+}
+
+/// Classes defined in the css input that were never found in scope, grouped by the `@layer` they
+/// are in, or by the rule that defines them when they aren't in any.
+pub struct Unused<'c> {
+    groups: BTreeMap<String, Vec<UnusedClass<'c>>>,
+}
+
+impl<'c> Unused<'c> {
+    pub fn new(audit: &Audit, collector: &'c ClassNamesCollector) -> Self {
+        let used: HashSet<&str> = audit
+            .usages
+            .keys()
+            .map(|(_, class, _)| class.as_str())
+            .collect();
+
+        let mut seen = HashSet::new();
+        let mut groups: BTreeMap<String, Vec<UnusedClass>> = BTreeMap::new();
+
+        for (class, origin) in collector.class_names.iter().zip(&collector.origins) {
+            if used.contains(&**class) || !seen.insert(&**class) {
+                continue;
+            }
+
+            let group = match &origin.layer {
+                Some(layer) => format!("@layer {layer}"),
+                None => origin.rule.clone(),
+            };
+
+            groups.entry(group).or_default().push(UnusedClass {
+                class,
+                rule: &origin.rule,
+                line: origin.line,
+            });
+        }
+
+        Self { groups }
+    }
+
+    pub fn write(&self, format: AuditFormat, mut out: impl Write) -> anyhow::Result<()> {
+        match format {
+            AuditFormat::Text => {
+                for (group, classes) in &self.groups {
+                    writeln!(out, "{group}")?;
+                    for c in classes {
+                        writeln!(out, "  {} (line {})", c.class, c.line)?;
+                    }
+                }
+            }
+            AuditFormat::Json => {
+                #[derive(Serialize)]
+                struct Group<'a, 'c> {
+                    group: &'a str,
+                    classes: &'a [UnusedClass<'c>],
+                }
+
+                let groups: Vec<_> = self
+                    .groups
+                    .iter()
+                    .map(|(group, classes)| Group { group, classes })
+                    .collect();
+
+                serde_json::to_writer_pretty(&mut out, &groups)?;
+                writeln!(out)?;
+            }
+            AuditFormat::Csv => {
+                writeln!(out, "group,class,rule,line")?;
+                for (group, classes) in &self.groups {
+                    for c in classes {
+                        writeln!(
+                            out,
+                            "{},{},{},{}",
+                            csv_field(group),
+                            csv_field(c.class),
+                            csv_field(c.rule),
+                            c.line
+                        )?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
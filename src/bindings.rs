@@ -0,0 +1,147 @@
+use std::collections::{HashMap, HashSet};
+
+use swc_ecma_ast::{Expr, Pat, VarDeclKind, VarDeclarator};
+use swc_ecma_visit::{VisitMut, VisitMutWith};
+
+use crate::report::ScopeEntry;
+use crate::transform::ApplyTailwindPrefix;
+
+/// Tracks the module level bindings referenced in scope, so that class strings assigned
+/// to them can be followed, e.g. `const base = "flex"; <div className={cn(base)} />`.
+#[derive(Default)]
+pub struct Bindings {
+    /// name -> number of declarations, in any scope
+    declarations: HashMap<String, usize>,
+    consts: HashSet<String>,
+    /// local name -> (module specifier, imported name)
+    imports: HashMap<String, (String, String)>,
+    /// exported name -> local name
+    exports: HashMap<String, String>,
+    /// name -> the scope of its first reference in scope
+    in_scope: HashMap<String, ScopeEntry>,
+    out_of_scope: HashSet<String>,
+}
+
+impl Bindings {
+    pub fn declare(&mut self, name: &str) {
+        *self.declarations.entry(name.to_string()).or_default() += 1;
+    }
+
+    /// Only consts initialized with a literal are followed; the arguments of a call, like the
+    /// config of `const button = cva({...})`, are left to the scopes that match the call.
+    pub fn declare_const(&mut self, n: &VarDeclarator, kind: VarDeclKind) {
+        if let (Pat::Ident(binding), Some(init), VarDeclKind::Const) = (&n.name, &n.init, kind) {
+            if is_literal(init) {
+                self.consts.insert(binding.id.sym.to_string());
+            }
+        }
+    }
+
+    /// `imported` is "default" for default imports.
+    pub fn import(&mut self, local: &str, src: &str, imported: &str) {
+        self.imports
+            .insert(local.to_string(), (src.to_string(), imported.to_string()));
+    }
+
+    /// `exported` is "default" for the default export.
+    pub fn export(&mut self, exported: &str, local: &str) {
+        self.exports.insert(exported.to_string(), local.to_string());
+    }
+
+    pub fn reference(&mut self, name: &str, scope: Option<&ScopeEntry>) {
+        match scope {
+            Some(scope) => {
+                self.in_scope
+                    .entry(name.to_string())
+                    .or_insert_with(|| scope.clone());
+            }
+            None => {
+                self.out_of_scope.insert(name.to_string());
+            }
+        }
+    }
+
+    fn is_followable(&self, name: &str) -> bool {
+        self.consts.contains(name)
+            && self.declarations.get(name) == Some(&1)
+            && !self.out_of_scope.contains(name)
+    }
+
+    /// The const bindings, declared only once, that are referenced in scope and nowhere else,
+    /// or that are exported under one of the `exported` names, which other modules use in scope;
+    /// with the scope they're referenced in.
+    pub fn to_follow(
+        &self,
+        exported: Option<&HashMap<String, ScopeEntry>>,
+    ) -> HashMap<String, ScopeEntry> {
+        let exported = exported
+            .into_iter()
+            .flatten()
+            .filter_map(|(name, scope)| self.exports.get(name).map(|local| (local, scope)));
+
+        self.in_scope
+            .iter()
+            .chain(exported)
+            .filter(|(name, _)| self.is_followable(name))
+            .map(|(name, scope)| (name.clone(), scope.clone()))
+            .collect()
+    }
+
+    /// The imported bindings that are referenced in scope and nowhere else, as
+    /// (module specifier, imported name, scope).
+    pub fn imports_to_follow(&self) -> impl Iterator<Item = (&str, &str, &ScopeEntry)> {
+        self.imports.iter().filter_map(|(local, (src, imported))| {
+            let scope = self.in_scope.get(local)?;
+            (!self.out_of_scope.contains(local)).then_some((src.as_str(), imported.as_str(), scope))
+        })
+    }
+
+    /// The imported bindings that are referenced out of scope, as (module specifier, imported name).
+    pub fn imports_out_of_scope(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.imports
+            .iter()
+            .filter(|(local, _)| self.out_of_scope.contains(*local))
+            .map(|(_, (src, imported))| (src.as_str(), imported.as_str()))
+    }
+
+    pub fn clear(&mut self) {
+        self.declarations.clear();
+        self.consts.clear();
+        self.imports.clear();
+        self.exports.clear();
+        self.in_scope.clear();
+        self.out_of_scope.clear();
+    }
+}
+
+/// A string, template, array or object literal, as is or `as const`.
+fn is_literal(expr: &Expr) -> bool {
+    match expr {
+        Expr::Lit(swc_ecma_ast::Lit::Str(_)) | Expr::Tpl(_) | Expr::Array(_) | Expr::Object(_) => {
+            true
+        }
+        Expr::Paren(e) => is_literal(&e.expr),
+        Expr::TsConstAssertion(e) => is_literal(&e.expr),
+        Expr::TsAs(e) => is_literal(&e.expr),
+        Expr::TsSatisfies(e) => is_literal(&e.expr),
+        _ => false,
+    }
+}
+
+/// Visits the initializers of the given bindings as if they were in scope.
+pub struct FollowBindings<'a, 's, 'cn, 'scopes> {
+    pub bindings: HashMap<String, ScopeEntry>,
+    pub apply: &'a mut ApplyTailwindPrefix<'s, 'cn, 'scopes>,
+}
+
+impl VisitMut for FollowBindings<'_, '_, '_, '_> {
+    fn visit_mut_var_declarator(&mut self, n: &mut VarDeclarator) {
+        if let (Pat::Ident(binding), Some(init)) = (&n.name, &mut n.init) {
+            if let Some(scope) = self.bindings.get(binding.id.sym.as_str()) {
+                self.apply.visit_in_scope(scope.clone(), init);
+            }
+        }
+
+        n.visit_mut_children_with(self);
+    }
+}
@@ -2,19 +2,38 @@ use std::path::PathBuf;
 
 use swc_common::errors::{ColorConfig, Handler};
 use swc_common::sync::Lrc;
-use swc_common::{FileName, SourceMap};
+use swc_common::{FileName, SourceMap, SourceMapper, Span};
 use swc_css::visit::{Visit, VisitWith};
 
 use swc_css::{ast::Rule, parser::parse_file};
 
+/// Where a class was defined in the css input.
+#[derive(Debug, Clone)]
+pub struct ClassOrigin {
+    /// The `@layer` the rule is in, nested layers joined with a '.'.
+    pub layer: Option<String>,
+    /// The selectors of the rule that defines the class.
+    pub rule: String,
+    pub line: usize,
+}
+
 pub struct ClassNamesCollector {
     pub class_names: Vec<cnat::Str>,
+    /// The origin of each class in `class_names`, at the same index.
+    pub origins: Vec<ClassOrigin>,
+    layers: Vec<String>,
+    rule: Option<Span>,
+    origin_spans: Vec<(Option<String>, Span)>,
 }
 
 impl ClassNamesCollector {
     pub fn new() -> Self {
         ClassNamesCollector {
             class_names: vec![],
+            origins: vec![],
+            layers: vec![],
+            rule: None,
+            origin_spans: vec![],
         }
     }
 
@@ -40,11 +59,71 @@ impl ClassNamesCollector {
 
         c.visit_with(&mut ccns);
 
+        ccns.origins = std::mem::take(&mut ccns.origin_spans)
+            .into_iter()
+            .map(|(layer, span)| ClassOrigin {
+                layer,
+                rule: cm
+                    .span_to_snippet(span)
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string(),
+                line: cm.lookup_char_pos(span.lo).line,
+            })
+            .collect();
+
         Ok(ccns)
     }
 }
 
+impl ClassNamesCollector {
+    fn push(&mut self, class_name: &str) {
+        self.class_names.push(class_name.into());
+
+        let layer = (!self.layers.is_empty()).then(|| self.layers.join("."));
+        self.origin_spans
+            .push((layer, self.rule.unwrap_or_default()));
+    }
+}
+
 impl Visit for ClassNamesCollector {
+    fn visit_at_rule(&mut self, n: &swc_css::ast::AtRule) {
+        let layer = match (&n.name, n.prelude.as_deref()) {
+            (
+                swc_css::ast::AtRuleName::Ident(name),
+                Some(swc_css::ast::AtRulePrelude::LayerPrelude(swc_css::ast::LayerPrelude::Name(
+                    layer,
+                ))),
+            ) if name.value == "layer" => Some(
+                layer
+                    .name
+                    .iter()
+                    .map(|i| i.value.as_str())
+                    .collect::<Vec<_>>()
+                    .join("."),
+            ),
+            _ => None,
+        };
+
+        if let Some(layer) = layer {
+            self.layers.push(layer);
+            n.visit_children_with(self);
+            self.layers.pop();
+        } else {
+            n.visit_children_with(self);
+        }
+    }
+
+    fn visit_qualified_rule(&mut self, n: &swc_css::ast::QualifiedRule) {
+        let outer = self.rule.replace(match &n.prelude {
+            swc_css::ast::QualifiedRulePrelude::SelectorList(s) => s.span,
+            swc_css::ast::QualifiedRulePrelude::RelativeSelectorList(s) => s.span,
+            swc_css::ast::QualifiedRulePrelude::ListOfComponentValues(s) => s.span,
+        });
+        n.visit_children_with(self);
+        self.rule = outer;
+    }
+
     fn visit_compound_selector(&mut self, n: &swc_css::ast::CompoundSelector) {
         let selectors = &n.subclass_selectors;
 
@@ -56,10 +135,10 @@ impl Visit for ClassNamesCollector {
             })
             .for_each(|s| {
                 if s.text.value.contains(':') {
-                    let cn = s.text.value.split(':').last().expect("should have at least one value after split, since empty selectors aren't allowed");
-                    self.class_names.push(cn.into());
+                    let cn = s.text.value.split(':').next_back().expect("should have at least one value after split, since empty selectors aren't allowed");
+                    self.push(cn);
                 } else {
-                    self.class_names.push(s.text.value.as_str().into());
+                    self.push(s.text.value.as_str());
                 }
             });
     }
@@ -0,0 +1,97 @@
+use std::collections::HashSet;
+
+use swc_common::comments::{Comment, SingleThreadedComments};
+use swc_common::{BytePos, SourceFile};
+
+/// The regions of a file that comments take out of, or force into, scope.
+///
+/// - `// cnat-ignore-next-line` leaves the strings on the next line alone.
+/// - `/* cnat-disable */` leaves every string alone until a `/* cnat-enable */`, or the end of the file.
+/// - `/* cnat */ "..."` puts the string right after it in scope.
+#[derive(Default)]
+pub struct Directives {
+    disabled: Vec<(BytePos, BytePos)>,
+    /// The start of every string that a `/* cnat */` comment comes right before.
+    forced: HashSet<BytePos>,
+}
+
+#[derive(PartialEq)]
+enum Directive {
+    IgnoreNextLine,
+    Disable,
+    Enable,
+    Force,
+}
+
+impl Directive {
+    /// The directive is the first word of a comment; the rest of it can say why.
+    fn parse(comment: &Comment) -> Option<Self> {
+        match comment.text.split_whitespace().next()? {
+            "cnat-ignore-next-line" => Some(Directive::IgnoreNextLine),
+            "cnat-disable" => Some(Directive::Disable),
+            "cnat-enable" => Some(Directive::Enable),
+            "cnat" => Some(Directive::Force),
+            _ => None,
+        }
+    }
+}
+
+impl Directives {
+    pub fn new(fm: &SourceFile, comments: &SingleThreadedComments) -> Self {
+        let (leading, trailing) = comments.borrow_all();
+
+        let forced = leading
+            .iter()
+            .filter(|(_, comments)| {
+                comments
+                    .last()
+                    .is_some_and(|c| Directive::parse(c) == Some(Directive::Force))
+            })
+            .map(|(pos, _)| *pos)
+            .collect();
+
+        let mut directives: Vec<_> = leading
+            .values()
+            .chain(trailing.values())
+            .flatten()
+            .filter_map(|c| Some((c.span, Directive::parse(c)?)))
+            .collect();
+        directives.sort_by_key(|(span, _)| span.lo);
+
+        let mut disabled = vec![];
+        let mut disabled_since = None;
+        for (span, directive) in directives {
+            match directive {
+                Directive::IgnoreNextLine => {
+                    let next_line = fm.lookup_line(span.hi).map(|line| line + 1);
+                    if let Some(line) = next_line.filter(|line| *line < fm.count_lines()) {
+                        disabled.push(fm.line_bounds(line));
+                    }
+                }
+                Directive::Disable => {
+                    disabled_since = disabled_since.or(Some(span.hi));
+                }
+                Directive::Enable => {
+                    if let Some(since) = disabled_since.take() {
+                        disabled.push((since, span.lo));
+                    }
+                }
+                Directive::Force => {}
+            }
+        }
+
+        if let Some(since) = disabled_since {
+            disabled.push((since, fm.end_pos));
+        }
+
+        Self { disabled, forced }
+    }
+
+    pub fn is_disabled(&self, pos: BytePos) -> bool {
+        self.disabled.iter().any(|(lo, hi)| *lo <= pos && pos < *hi)
+    }
+
+    pub fn is_forced(&self, pos: BytePos) -> bool {
+        self.forced.contains(&pos)
+    }
+}
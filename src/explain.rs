@@ -0,0 +1,184 @@
+use std::io::Write;
+
+use swc_common::{BytePos, SourceMap};
+
+use crate::report::ScopeEntry;
+
+/// Records, for every string literal visited, the scopes it was in and how each of its
+/// tokens matched the css input, to tell why it was or wasn't transformed.
+#[derive(Default)]
+pub struct Explain {
+    /// The scopes entered to get to the node being visited; `None` for an exclusion.
+    chain: Vec<Option<ScopeEntry>>,
+    pending: Vec<PendingLiteral>,
+    literals: Vec<Literal>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LiteralKind {
+    Str,
+    /// A string in a `sel` scope.
+    Selector,
+    /// A template literal, whose classes next to a `${…}` are left alone.
+    Template,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenMatch {
+    /// The class, without its variants, is in the css input.
+    Class,
+    /// The class already has the prefix.
+    Prefixed,
+    /// Not in the css input.
+    Unknown,
+    /// Joined to a `${…}` of a template, so only part of a class.
+    Dynamic,
+}
+
+/// A literal whose position hasn't been resolved to a line and column yet.
+struct PendingLiteral {
+    pos: BytePos,
+    literal: Literal,
+}
+
+struct Literal {
+    line: usize,
+    column: usize,
+    kind: LiteralKind,
+    text: String,
+    chain: Vec<Option<ScopeEntry>>,
+    is_in_scope: bool,
+    /// Whether a comment directive leaves the literal alone.
+    is_disabled: bool,
+    tokens: Vec<(String, TokenMatch)>,
+}
+
+impl Explain {
+    pub fn enter(&mut self, scope: Option<ScopeEntry>) {
+        self.chain.push(scope);
+    }
+
+    pub fn exit(&mut self) {
+        self.chain.pop();
+    }
+
+    pub fn literal(
+        &mut self,
+        pos: BytePos,
+        kind: LiteralKind,
+        text: &str,
+        is_in_scope: bool,
+        is_disabled: bool,
+        tokens: Vec<(String, TokenMatch)>,
+    ) {
+        self.pending.push(PendingLiteral {
+            pos,
+            literal: Literal {
+                line: 0,
+                column: 0,
+                kind,
+                text: text.to_string(),
+                chain: self.chain.clone(),
+                is_in_scope,
+                is_disabled,
+                tokens,
+            },
+        });
+    }
+
+    /// Resolves the positions of the literals visited in a file.
+    /// A literal visited more than once, like the initializer of a binding that's followed,
+    /// keeps the visit that had it in scope.
+    pub fn record(&mut self, cm: &SourceMap) {
+        let mut pending = std::mem::take(&mut self.pending);
+        pending.sort_by_key(|p| p.pos);
+
+        let mut last_pos = None;
+        for p in pending {
+            let loc = cm.lookup_char_pos(p.pos);
+            let literal = Literal {
+                line: loc.line,
+                column: loc.col.0 + 1,
+                ..p.literal
+            };
+
+            if last_pos == Some(p.pos) {
+                let previous = self.literals.last_mut().expect("a literal was recorded");
+                if !previous.is_in_scope && literal.is_in_scope {
+                    *previous = literal;
+                }
+                continue;
+            }
+
+            last_pos = Some(p.pos);
+            self.literals.push(literal);
+        }
+    }
+
+    pub fn write(&self, mut out: impl Write) -> anyhow::Result<()> {
+        for literal in &self.literals {
+            match literal.kind {
+                LiteralKind::Template => writeln!(
+                    out,
+                    "{}:{} `{}`",
+                    literal.line, literal.column, literal.text
+                )?,
+                _ => writeln!(
+                    out,
+                    "{}:{} {:?}",
+                    literal.line, literal.column, literal.text
+                )?,
+            }
+
+            let chain: Vec<_> = literal
+                .chain
+                .iter()
+                .map(|scope| match scope {
+                    Some(scope) => format!("{}:{}", scope.variant, scope.value),
+                    None => "excluded".to_string(),
+                })
+                .collect();
+
+            match (literal.is_in_scope, chain.is_empty()) {
+                (true, _) => writeln!(out, "    in scope: {}", chain.join(" > "))?,
+                (false, true) => writeln!(out, "    not in scope: no scope matched")?,
+                (false, false) => writeln!(out, "    not in scope: {}", chain.join(" > "))?,
+            }
+
+            if literal.is_disabled {
+                writeln!(out, "    left alone by a cnat comment directive")?;
+            }
+
+            match literal.kind {
+                LiteralKind::Selector => writeln!(
+                    out,
+                    "    a css selector, only its class selectors are prefixed"
+                )?,
+                LiteralKind::Template => writeln!(
+                    out,
+                    "    a template literal, the classes joined to a ${{…}} are left alone"
+                )?,
+                LiteralKind::Str => {}
+            }
+
+            let width = literal
+                .tokens
+                .iter()
+                .map(|(token, _)| token.len())
+                .max()
+                .unwrap_or(0);
+
+            for (token, matched) in &literal.tokens {
+                let matched = match matched {
+                    TokenMatch::Class => "in the css",
+                    TokenMatch::Prefixed => "prefixed already",
+                    TokenMatch::Unknown => "not in the css",
+                    TokenMatch::Dynamic => "joined to a ${…}",
+                };
+                writeln!(out, "    {token:width$}  {matched}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
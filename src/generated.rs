@@ -0,0 +1,105 @@
+use std::path::{Path, PathBuf};
+
+/// How many lines, from the top of a file, to look for markers in.
+const HEADER_LINES: usize = 5;
+
+/// Files with longer lines than this, on average, are taken as minified.
+const MAX_AVERAGE_LINE_LENGTH: usize = 300;
+
+/// Files smaller than this are never taken as minified, a short file can be a single long line.
+const MIN_MINIFIED_SIZE: usize = 2048;
+
+/// Skips the files that look generated or minified, like graphql types and bundled vendor code.
+#[derive(Clone)]
+pub struct SkipGenerated {
+    /// Files larger than this, in bytes, are skipped.
+    pub max_file_size: u64,
+    /// List every file skipped, and why.
+    pub verbose: bool,
+}
+
+impl SkipGenerated {
+    /// Removes the files that look generated or minified.
+    pub fn retain(&self, files: &mut Vec<PathBuf>) {
+        files.retain(|file| match self.reason(file) {
+            Some(reason) => {
+                if self.verbose {
+                    eprintln!("[INFO] skipped {}, {reason}", file.display());
+                }
+                false
+            }
+            None => true,
+        });
+    }
+
+    /// Why the file looks generated or minified, if it does.
+    fn reason(&self, file: &Path) -> Option<String> {
+        if file.to_string_lossy().ends_with(".min.js") {
+            return Some("minified: named *.min.js".to_string());
+        }
+
+        let size = std::fs::metadata(file).ok()?.len();
+        if size > self.max_file_size {
+            return Some(format!(
+                "too large: {size} bytes, more than {}",
+                self.max_file_size
+            ));
+        }
+
+        let contents = std::fs::read(file).ok()?;
+        reason_in_contents(&String::from_utf8_lossy(&contents))
+    }
+}
+
+fn reason_in_contents(contents: &str) -> Option<String> {
+    for line in contents.lines().take(HEADER_LINES) {
+        if line.contains("@generated") {
+            return Some("generated: has an @generated marker".to_string());
+        }
+
+        if is_bare_eslint_disable(line) {
+            return Some("generated: has an eslint-disable header".to_string());
+        }
+    }
+
+    let lines = contents.lines().count().max(1);
+    let average = contents.len() / lines;
+    if contents.len() >= MIN_MINIFIED_SIZE && average > MAX_AVERAGE_LINE_LENGTH {
+        return Some(format!(
+            "minified: lines are {average} characters long on average"
+        ));
+    }
+
+    None
+}
+
+/// `/* eslint-disable */`, without a list of rules: hand-written files disable a rule or two,
+/// like `/* eslint-disable react/no-danger */`, and `eslint-disable-next-line` only covers a line.
+fn is_bare_eslint_disable(line: &str) -> bool {
+    line.split_once("eslint-disable").is_some_and(|(_, rest)| {
+        let rest = rest.trim();
+        rest.is_empty() || rest == "*/"
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_finds_generated_and_minified_contents() {
+        assert!(
+            reason_in_contents("// @generated by graphql-codegen\nexport type A = {};").is_some()
+        );
+        assert!(reason_in_contents("/* eslint-disable */\nexport const a = 1;").is_some());
+        assert!(reason_in_contents(&"var a=1;".repeat(400)).is_some());
+
+        assert!(reason_in_contents("// eslint-disable-next-line\nconst a = 1;").is_none());
+        assert!(
+            reason_in_contents("/* eslint-disable react/no-danger */\nexport const a = 1;")
+                .is_none()
+        );
+        assert!(reason_in_contents(&"const a = 1;\n".repeat(400)).is_none());
+        assert!(reason_in_contents(&"a".repeat(1000)).is_none());
+    }
+}
@@ -0,0 +1,205 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Context};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+
+/// Where the journals of the runs are kept, in the directory `cnat` runs from.
+pub const JOURNAL_DIR: &str = ".cnat";
+
+/// Records the original content of every file a run modifies, so `cnat undo` can restore it.
+///
+/// The original bytes of a file are saved before it's written to, so a run that's stopped
+/// midway can still be undone.
+pub struct Journal {
+    dir: PathBuf,
+    run: Run,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Run {
+    id: String,
+    files: Vec<Entry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    path: PathBuf,
+    /// The file, under the run's directory, with the original bytes.
+    backup: PathBuf,
+    original_hash: String,
+    new_hash: String,
+}
+
+impl Journal {
+    /// A journal for a new run, under `root`. Nothing is written until a file is recorded.
+    pub fn new(root: &Path) -> Self {
+        let since_epoch = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        // sorts by time, the process id tells apart runs started in the same millisecond
+        let id = format!("{:013}-{}", since_epoch.as_millis(), std::process::id());
+
+        Self {
+            dir: root.join("runs").join(&id),
+            run: Run { id, files: vec![] },
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.run.id
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.run.files.is_empty()
+    }
+
+    /// Saves the original content of a file about to be overwritten with `new`.
+    /// The path is resolved, symlinks included, as `write_atomically` writes to their target.
+    pub fn record(&mut self, file: &Path, original: &[u8], new: &[u8]) -> anyhow::Result<()> {
+        let path = file
+            .canonicalize()
+            .with_context(|| format!("failed to resolve {}", file.display()))?;
+
+        let backup = PathBuf::from(self.run.files.len().to_string());
+        std::fs::create_dir_all(&self.dir).context("failed to create the run journal")?;
+        write_atomically(&self.dir.join(&backup), original)
+            .context("failed to back up the original file")?;
+
+        self.run.files.push(Entry {
+            path,
+            backup,
+            original_hash: hash(original),
+            new_hash: hash(new),
+        });
+
+        let run = serde_json::to_vec_pretty(&self.run)?;
+        write_atomically(&self.dir.join("run.json"), &run)
+            .context("failed to write the run journal")
+    }
+}
+
+/// Writes to a temporary file next to `path`, then renames it into place, so `path` has either
+/// the old or the new content, even if the process is stopped midway.
+/// A symlink is left in place, its target is written to instead.
+pub fn write_atomically(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let target;
+    let path = if path.is_symlink() {
+        target = path.canonicalize()?;
+        &target
+    } else {
+        path
+    };
+
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let temp = path.with_file_name(format!(".{file_name}.cnat.tmp"));
+
+    let written = (|| {
+        let mut file = std::fs::File::create(&temp)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+
+        if let Ok(metadata) = std::fs::metadata(path) {
+            std::fs::set_permissions(&temp, metadata.permissions())?;
+        }
+
+        std::fs::rename(&temp, path)
+    })();
+
+    if written.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+
+    written
+}
+
+/// The ids of the runs that can be undone, oldest first.
+pub fn runs(root: &Path) -> anyhow::Result<Vec<String>> {
+    let dir = root.join("runs");
+    if !dir.is_dir() {
+        return Ok(vec![]);
+    }
+
+    let mut runs = vec![];
+    for entry in std::fs::read_dir(&dir).context("failed to read the run journals")? {
+        let entry = entry?;
+        if entry.path().join("run.json").is_file() {
+            runs.push(entry.file_name().to_string_lossy().to_string());
+        }
+    }
+
+    runs.sort();
+    Ok(runs)
+}
+
+/// Restores the files a run modified, the last run if `run_id` is `None`.
+/// A file changed since the run is left alone, unless `force`.
+/// The run's journal is removed once every file is back to its original content.
+/// Returns the number of files restored.
+pub fn undo(root: &Path, run_id: Option<&str>, force: bool) -> anyhow::Result<usize> {
+    let run_id = match run_id {
+        Some(id) => id.to_string(),
+        None => runs(root)?
+            .pop()
+            .ok_or_else(|| anyhow!("no run to undo in {}", root.display()))?,
+    };
+
+    let dir = root.join("runs").join(&run_id);
+    let run = std::fs::read(dir.join("run.json"))
+        .with_context(|| format!("no run {run_id} in {}", root.display()))?;
+    let run: Run = serde_json::from_slice(&run).context("failed to read the run journal")?;
+
+    let mut restored = 0;
+    let mut confirmed = 0;
+    for entry in run.files.iter().rev() {
+        let current_hash = std::fs::read(&entry.path).ok().map(|c| hash(&c));
+
+        if current_hash.as_deref() == Some(&entry.original_hash) {
+            confirmed += 1;
+            continue;
+        }
+
+        if current_hash.as_deref() != Some(&entry.new_hash) && !force {
+            eprintln!(
+                "{} {} changed since the run, pass --force to restore it anyway",
+                "[WARN]".yellow(),
+                entry.path.display()
+            );
+            continue;
+        }
+
+        let original = std::fs::read(dir.join(&entry.backup))
+            .with_context(|| format!("missing the backup of {}", entry.path.display()))?;
+        write_atomically(&entry.path, &original)
+            .with_context(|| format!("failed to restore {}", entry.path.display()))?;
+
+        eprintln!("[INFO] restored {}", entry.path.display());
+        restored += 1;
+
+        let restored_hash = std::fs::read(&entry.path).ok().map(|c| hash(&c));
+        if restored_hash.as_deref() == Some(&entry.original_hash) {
+            confirmed += 1;
+        }
+    }
+
+    if confirmed == run.files.len() {
+        std::fs::remove_dir_all(&dir).context("failed to remove the run journal")?;
+    } else {
+        eprintln!(
+            "{} kept the journal of run {run_id}, not every file could be restored",
+            "[WARN]".yellow()
+        );
+    }
+
+    Ok(restored)
+}
+
+/// FNV-1a, to tell whether a file changed since a run.
+fn hash(bytes: &[u8]) -> String {
+    let hash = bytes.iter().fold(0xcbf29ce484222325_u64, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{hash:016x}")
+}
@@ -30,6 +30,6 @@ impl From<&str> for Str {
 
 impl PartialEq<str> for Str {
     fn eq(&self, other: &str) -> bool {
-        return &*self.0 == other;
+        &*self.0 == other
     }
 }
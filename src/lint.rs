@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+
+use cnat::scope::glob_regex;
+
+/// Finds classes, in scope, that aren't defined in the css input.
+pub struct UnknownClasses {
+    allowlist: Vec<regex::Regex>,
+    suggestions: HashMap<String, Option<cnat::Str>>,
+}
+
+impl UnknownClasses {
+    /// Classes matching any of the `allowlist` patterns are never reported.
+    /// A pattern may have any number of `*` wildcards, as in scope values.
+    pub fn new(allowlist: Vec<String>) -> Self {
+        Self {
+            allowlist: allowlist
+                .iter()
+                .map(|pattern| glob_regex(pattern).expect("an escaped glob is a valid regex"))
+                .collect(),
+            suggestions: HashMap::new(),
+        }
+    }
+
+    pub fn is_allowed(&self, class: &str) -> bool {
+        self.allowlist.iter().any(|pattern| pattern.is_match(class))
+    }
+
+    /// The class name closest to `class` by edit distance, if it's close enough to be a typo.
+    pub fn suggest(&mut self, class: &str, class_names: &[cnat::Str]) -> Option<&str> {
+        self.suggestions
+            .entry(class.to_string())
+            .or_insert_with(|| {
+                let max_distance = (class.chars().count() / 3).clamp(1, 3);
+                class_names
+                    .iter()
+                    .map(|name| (strsim::levenshtein(class, name), name))
+                    .filter(|(distance, _)| *distance <= max_distance)
+                    .min_by_key(|(distance, _)| *distance)
+                    .map(|(_, name)| name.clone())
+            })
+            .as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_matches_wildcards() {
+        let allowlist = ["group", "Mui*", "*-icon", "data-*-class"];
+        let lint = UnknownClasses::new(allowlist.map(String::from).to_vec());
+
+        assert!(lint.is_allowed("group"));
+        assert!(!lint.is_allowed("group/item"));
+        assert!(lint.is_allowed("MuiButton-startIcon"));
+        assert!(lint.is_allowed("btn-icon"));
+        assert!(lint.is_allowed("data-icon-class"));
+        assert!(!lint.is_allowed("data-class"));
+
+        let lint = UnknownClasses::new(vec!["*".to_string()]);
+        assert!(lint.is_allowed("anything"));
+    }
+
+    #[test]
+    fn it_suggests_close_class_names() {
+        let class_names: Vec<cnat::Str> = vec!["text-gray-500".into(), "text-sm".into()];
+        let mut lint = UnknownClasses::new(vec![]);
+
+        assert_eq!(
+            lint.suggest("text-grey-500", &class_names),
+            Some("text-gray-500")
+        );
+        assert_eq!(lint.suggest("untouched", &class_names), None);
+    }
+}
@@ -0,0 +1,144 @@
+/// The text of a string literal as written in the source, between the quotes, and where each
+/// byte of its value comes from, so a rewrite keeps every escape exactly as written.
+pub struct RawStr<'a> {
+    pub raw: &'a str,
+    /// raw byte offset of every value byte, and of the end of the value
+    offsets: Vec<usize>,
+}
+
+impl<'a> RawStr<'a> {
+    /// `None` when the raw text can't be mapped to `value`, like html entities in jsx.
+    pub fn new(raw: &'a str, value: &str) -> Option<Self> {
+        if raw == value {
+            return Some(Self {
+                raw,
+                offsets: (0..=raw.len()).collect(),
+            });
+        }
+
+        let (cooked, offsets) = cook(raw)?;
+        (cooked == value).then_some(Self { raw, offsets })
+    }
+
+    /// The offset, into the raw text, of the byte of the value at `offset`.
+    pub fn raw_offset(&self, offset: usize) -> usize {
+        self.offsets[offset]
+    }
+
+    /// The raw text, with `insertion` inserted before each of the bytes of the value at `offsets`.
+    pub fn insert_at(
+        &self,
+        offsets: impl DoubleEndedIterator<Item = usize>,
+        insertion: &str,
+    ) -> String {
+        let mut raw = self.raw.to_string();
+        for offset in offsets.rev() {
+            raw.insert_str(self.raw_offset(offset), insertion);
+        }
+        raw
+    }
+}
+
+/// The tokens of a string's value, separated by any ascii whitespace, with their byte offsets.
+pub fn tokens(value: &str) -> impl Iterator<Item = (usize, &str)> {
+    value
+        .split(|c: char| c.is_ascii_whitespace())
+        .scan(0, |offset, token| {
+            let token_offset = *offset;
+            // + 1 for the separator, ascii whitespace is a single byte
+            *offset += token.len() + 1;
+            Some((token_offset, token))
+        })
+        .filter(|(_, token)| !token.is_empty())
+}
+
+/// The value of a js string from its raw text, and the raw offset of each byte of the value.
+fn cook(raw: &str) -> Option<(String, Vec<usize>)> {
+    let mut value = String::with_capacity(raw.len());
+    let mut offsets = Vec::with_capacity(raw.len() + 1);
+    let mut chars = raw.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        let cooked = match c {
+            '\\' => match chars.next()?.1 {
+                'n' => Some('\n'),
+                't' => Some('\t'),
+                'r' => Some('\r'),
+                'b' => Some('\u{8}'),
+                'f' => Some('\u{c}'),
+                'v' => Some('\u{b}'),
+                '0' if !chars.peek().is_some_and(|(_, c)| c.is_ascii_digit()) => Some('\0'),
+                // legacy octal escapes
+                '0'..='9' => return None,
+                'x' => {
+                    let hex: String = (0..2)
+                        .filter_map(|_| chars.next())
+                        .map(|(_, c)| c)
+                        .collect();
+                    Some(char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?)
+                }
+                'u' => {
+                    let hex: String = if chars.peek().is_some_and(|(_, c)| *c == '{') {
+                        chars.next();
+                        chars
+                            .by_ref()
+                            .map(|(_, c)| c)
+                            .take_while(|c| *c != '}')
+                            .collect()
+                    } else {
+                        (0..4)
+                            .filter_map(|_| chars.next())
+                            .map(|(_, c)| c)
+                            .collect()
+                    };
+                    // lone surrogates, of a surrogate pair, aren't chars
+                    Some(char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?)
+                }
+                // line continuations
+                '\r' => {
+                    chars.next_if(|(_, c)| *c == '\n');
+                    None
+                }
+                '\n' | '\u{2028}' | '\u{2029}' => None,
+                c => Some(c),
+            },
+            c => Some(c),
+        };
+
+        if let Some(c) = cooked {
+            offsets.extend(std::iter::repeat_n(i, c.len_utf8()));
+            value.push(c);
+        }
+    }
+
+    offsets.push(raw.len());
+    Some((value, offsets))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_maps_values_to_raw_text() {
+        let raw = r"it\'s p-2";
+        let s = RawStr::new(raw, "it's p-2").unwrap();
+        assert_eq!(s.raw_offset(5), 6);
+        assert_eq!(s.insert_at([5].into_iter(), "tw-"), r"it\'s tw-p-2");
+
+        let raw = r"flex p-2 \x70-4";
+        let s = RawStr::new(raw, "flex p-2 p-4").unwrap();
+        assert_eq!(
+            s.insert_at([0, 5, 9].into_iter(), "tw-"),
+            r"tw-flex tw-p-2 tw-\x70-4"
+        );
+
+        assert!(RawStr::new("a &amp; b", "a & b").is_none());
+    }
+
+    #[test]
+    fn it_splits_tokens_on_any_ascii_whitespace() {
+        let tokens: Vec<_> = tokens("\n  flex\tp-2  \r\nmd:p-4 ").collect();
+        assert_eq!(tokens, [(3, "flex"), (8, "p-2"), (15, "md:p-4")]);
+    }
+}
@@ -1,15 +1,36 @@
+mod audit;
+mod bindings;
 mod collect;
+mod directives;
+mod explain;
+mod generated;
+mod journal;
+mod lint;
+mod literal;
+mod modules;
+mod presets;
+mod report;
+mod suggest;
 mod transform;
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use anyhow::anyhow;
+use anyhow::{anyhow, Context};
+use audit::AuditFormat;
 use clap::{crate_name, Args, CommandFactory, Parser, Subcommand, ValueHint};
 use cnat::scope::Scope;
 use collect::ClassNamesCollector;
 use colored::Colorize;
+use report::ReportFormat;
 
-use crate::transform::ApplyTailwindPrefix;
+use crate::audit::{Audit, Unused};
+use crate::explain::Explain;
+use crate::generated::SkipGenerated;
+use crate::journal::{Journal, JOURNAL_DIR};
+use crate::lint::UnknownClasses;
+use crate::presets::Preset;
+use crate::suggest::Suggest;
+use crate::transform::{collect_files, ApplyTailwindPrefix};
 
 /// Systematically apply certain modifications to classes, class names, used
 /// in your frontend codebase.
@@ -25,6 +46,21 @@ enum Command {
     /// Apply a prefix to all the tailwind classes in every js file in a project.
     Prefix(PrefixArgs),
 
+    /// Show how often, where, and under which scopes the classes are used, without changing any file.
+    Audit(AuditArgs),
+
+    /// Work with the scopes that decide where classes are found.
+    Scopes {
+        #[command(subcommand)]
+        command: ScopesCommand,
+    },
+
+    /// Show why every string literal in a file was, or wasn't, transformed: the scopes it's in, and how its tokens match the css file.
+    Explain(ExplainArgs),
+
+    /// Restore the files modified by a run of `prefix`, the last one by default.
+    Undo(UndoArgs),
+
     /// Generate completions for a specified shell
     Completion {
         // The shell for which to generate completions
@@ -32,30 +68,182 @@ enum Command {
     },
 }
 
+#[derive(Subcommand)]
+enum ScopesCommand {
+    /// Show the scopes that each preset adds.
+    ListPresets,
+
+    /// Find the strings, out of scope, made mostly of classes, and suggest the scopes that would take them in.
+    Suggest(SuggestArgs),
+}
+
 #[derive(Args)]
-struct PrefixArgs {
+struct ScopeArgs {
+    /// Define scope within which prefixing happens. Example: --scopes 'att:className,*ClassName prop:classes fn:cva'
+    #[arg(short, long, num_args = 1.., value_delimiter = ' ', default_value = "att:class,className fn:createElement,React.createElement")]
+    scopes: Vec<Scope>,
+
+    /// Add the scopes of popular libraries to the ones above. See `cnat scopes list-presets`. Example: --preset shadcn,headlessui
+    #[arg(long, value_enum, value_delimiter = ',')]
+    preset: Vec<Preset>,
+}
+
+impl ScopeArgs {
+    fn scopes(&self) -> Vec<Scope> {
+        presets::merge(&self.scopes, &self.preset)
+    }
+}
+
+#[derive(Args)]
+struct InputArgs {
     /// The output css file generated by calling `npx tailwindcss -i input.css -o output.css`
     #[arg(short = 'i', value_hint = ValueHint::FilePath)]
     css_file: PathBuf,
 
+    #[command(flatten)]
+    scopes: ScopeArgs,
+
+    /// The directories in which to find js/ts files.
+    #[arg(value_hint = ValueHint::DirPath)]
+    contexts: Vec<PathBuf>,
+
+    /// Skip the files larger than this, in bytes.
+    #[arg(long, default_value_t = 1_000_000)]
+    max_file_size: u64,
+
+    /// Don't skip the files that look generated or minified, or are larger than --max-file-size.
+    #[arg(long)]
+    include_generated: bool,
+
+    /// List the files skipped, and why.
+    #[arg(short, long)]
+    verbose: bool,
+}
+
+impl InputArgs {
+    fn check_contexts(&self) -> anyhow::Result<()> {
+        for context in &self.contexts {
+            if !context.is_dir() {
+                return Err(anyhow!(
+                    "context should be a directory, got {}",
+                    context.display()
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn skip_generated(&self) -> Option<SkipGenerated> {
+        (!self.include_generated).then_some(SkipGenerated {
+            max_file_size: self.max_file_size,
+            verbose: self.verbose,
+        })
+    }
+}
+
+#[derive(Args)]
+struct PrefixArgs {
     /// The prefix to apply to all the tailwind class names found
     #[arg(short, long)]
     prefix: String,
 
-    /// Define scope within which prefixing happens. Example: --scopes 'att:className,*ClassName prop:classes fn:cva'
-    #[arg(short, long, num_args = 1.., value_delimiter = ' ', default_value = "att:class,className fn:createElement")]
-    scopes: Vec<Scope>,
+    #[command(flatten)]
+    input: InputArgs,
 
-    /// The directories in which to find js/ts files.
-    #[arg(value_hint = ValueHint::DirPath)]
-    contexts: Vec<PathBuf>,
+    /// Don't write any files; report every class that still needs the prefix and fail if there is any.
+    #[arg(long)]
+    check: bool,
+
+    /// Don't save the original files under `.cnat/`, the run can't be undone with `cnat undo`.
+    #[arg(long)]
+    no_journal: bool,
+
+    /// Print a report of every replacement made, and every finding, in the given format.
+    #[arg(long, value_enum)]
+    report: Option<ReportFormat>,
+
+    /// Write the report to this file instead of stdout.
+    #[arg(long, requires = "report", value_hint = ValueHint::FilePath)]
+    report_file: Option<PathBuf>,
+
+    /// Warn about classes, in scope, that aren't in the css file, like typos and removed utilities.
+    #[arg(long)]
+    unknown_classes: bool,
+
+    /// Classes defined outside of the css file, not to warn about. A '*' matches anything. Example: --allow-unknown 'group,peer,Mui*'
+    #[arg(long, value_delimiter = ',', requires = "unknown_classes")]
+    allow_unknown: Vec<String>,
+}
+
+#[derive(Args)]
+struct AuditArgs {
+    #[command(flatten)]
+    input: InputArgs,
+
+    /// The format in which to print the statistics.
+    #[arg(short, long, value_enum, default_value = "text")]
+    format: AuditFormat,
+
+    /// List the classes defined in the css file that are never used in scope instead, grouped by `@layer` or rule.
+    #[arg(long)]
+    unused: bool,
+}
+
+#[derive(Args)]
+struct SuggestArgs {
+    #[command(flatten)]
+    input: InputArgs,
+}
+
+#[derive(Args)]
+struct ExplainArgs {
+    /// The output css file generated by calling `npx tailwindcss -i input.css -o output.css`
+    #[arg(short = 'i', value_hint = ValueHint::FilePath)]
+    css_file: PathBuf,
+
+    #[command(flatten)]
+    scopes: ScopeArgs,
+
+    /// The prefix, to tell the classes that already have it.
+    #[arg(short, long, default_value = "")]
+    prefix: String,
+
+    /// The js/ts file to explain.
+    #[arg(value_hint = ValueHint::FilePath)]
+    file: PathBuf,
+
+    /// The directory whose files import from the file, to follow the bindings it exports, as `prefix` does.
+    #[arg(long, default_value = ".", value_hint = ValueHint::DirPath)]
+    context: PathBuf,
+}
+
+#[derive(Args)]
+struct UndoArgs {
+    /// The id of the run to undo, see `--list`.
+    run_id: Option<String>,
+
+    /// List the runs that can be undone, oldest first.
+    #[arg(long, conflicts_with = "run_id")]
+    list: bool,
+
+    /// Restore the files changed since the run too.
+    #[arg(long)]
+    force: bool,
 }
 
 fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
 
-    let cli = match cli.command {
-        Command::Prefix(cli) => cli,
+    match cli.command {
+        Command::Prefix(args) => prefix(args),
+        Command::Audit(args) => audit(args),
+        Command::Scopes { command } => match command {
+            ScopesCommand::ListPresets => presets::list(std::io::stdout().lock()),
+            ScopesCommand::Suggest(args) => suggest(args),
+        },
+        Command::Explain(args) => explain(args),
+        Command::Undo(args) => undo(args),
         Command::Completion { shell } => {
             clap_complete::generate(
                 shell,
@@ -63,34 +251,182 @@ fn main() -> anyhow::Result<()> {
                 crate_name!(),
                 &mut std::io::stdout(),
             );
-            return Ok(());
+            Ok(())
+        }
+    }
+}
+
+fn prefix(cli: PrefixArgs) -> anyhow::Result<()> {
+    cli.input.check_contexts()?;
+    let scopes = cli.input.scopes.scopes();
+    let skip_generated = cli.input.skip_generated();
+
+    let c = ClassNamesCollector::parse(cli.input.css_file)?;
+
+    eprintln!("[INFO] extracted selectors");
+    let report_to_stdout = cli.report.is_some() && cli.report_file.is_none();
+    if !report_to_stdout {
+        println!("{:?}", c.class_names);
+    }
+
+    let mut ppc = ApplyTailwindPrefix::new(&cli.prefix, &c.class_names, &scopes);
+    ppc.dry_run = cli.check;
+    ppc.journal = (!cli.check && !cli.no_journal).then(|| Journal::new(Path::new(JOURNAL_DIR)));
+    ppc.skip_generated = skip_generated;
+    ppc.check = cli.check;
+    ppc.unknown_classes = cli
+        .unknown_classes
+        .then(|| UnknownClasses::new(cli.allow_unknown));
+
+    let count = ppc.prefix_all_classes_in_dirs(&cli.input.contexts)?;
+
+    for finding in ppc.report.findings() {
+        eprintln!("{finding}");
+    }
+
+    if let Some(journal) = ppc.journal.as_ref().filter(|j| !j.is_empty()) {
+        eprintln!(
+            "[INFO] run {}, revert it with `cnat undo {}`",
+            journal.id(),
+            journal.id()
+        );
+    }
+
+    if cli.check {
+        eprintln!(
+            "{}",
+            format!("[DONE] {} files would be tranformed.", count).yellow()
+        );
+    } else {
+        eprintln!(
+            "{}",
+            format!("[DONE] {} files were tranformed.", count).green()
+        );
+    }
+
+    if let Some(format) = cli.report {
+        match &cli.report_file {
+            Some(path) => {
+                let file = std::fs::File::create(path).context("failed to create report file")?;
+                ppc.report.write(format, std::io::BufWriter::new(file))?;
+            }
+            None => ppc.report.write(format, std::io::stdout().lock())?,
+        }
+    }
+
+    if cli.check && ppc.report.findings().iter().any(|f| f.kind.is_error()) {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+fn audit(cli: AuditArgs) -> anyhow::Result<()> {
+    cli.input.check_contexts()?;
+    let scopes = cli.input.scopes.scopes();
+    let skip_generated = cli.input.skip_generated();
+
+    let c = ClassNamesCollector::parse(cli.input.css_file)?;
+
+    eprintln!("[INFO] extracted selectors");
+
+    let mut ppc = ApplyTailwindPrefix::new("", &c.class_names, &scopes);
+    ppc.dry_run = true;
+    ppc.skip_generated = skip_generated;
+
+    ppc.prefix_all_classes_in_dirs(&cli.input.contexts)?;
+
+    let audit = Audit::new(&ppc.report);
+
+    if cli.unused {
+        Unused::new(&audit, &c).write(cli.format, std::io::stdout().lock())
+    } else {
+        audit.write(cli.format, std::io::stdout().lock())
+    }
+}
+
+fn suggest(cli: SuggestArgs) -> anyhow::Result<()> {
+    cli.input.check_contexts()?;
+    let scopes = cli.input.scopes.scopes();
+    let skip_generated = cli.input.skip_generated();
+
+    let c = ClassNamesCollector::parse(cli.input.css_file)?;
+
+    eprintln!("[INFO] extracted selectors");
+
+    let mut ppc = ApplyTailwindPrefix::new("", &c.class_names, &scopes);
+    ppc.dry_run = true;
+    ppc.skip_generated = skip_generated.clone();
+
+    ppc.prefix_all_classes_in_dirs(&cli.input.contexts)?;
+
+    let mut suggest = Suggest::new(&c.class_names, &scopes, &ppc.report);
+
+    for context in &cli.input.contexts {
+        let mut files = collect_files(context);
+        if let Some(skip) = &skip_generated {
+            // the skipped files were listed already
+            let skip = SkipGenerated {
+                verbose: false,
+                ..skip.clone()
+            };
+            skip.retain(&mut files);
         }
-    };
-
-    for context in &cli.contexts {
-        if !context.is_dir() {
-            return Err(anyhow!(
-                "context should be a directory, got {}",
-                context.display()
-            ));
+
+        for filepath in files {
+            if let Err(err) = suggest.scan_file(&filepath) {
+                eprintln!(
+                    "{} failed to process file, {}: {err:#}",
+                    "[ERROR]".red(),
+                    filepath.display()
+                )
+            }
         }
     }
 
+    suggest.write(std::io::stdout().lock())
+}
+
+fn explain(cli: ExplainArgs) -> anyhow::Result<()> {
+    if !cli.context.is_dir() {
+        return Err(anyhow!(
+            "context should be a directory, got {}",
+            cli.context.display()
+        ));
+    }
+    let scopes = cli.scopes.scopes();
+
     let c = ClassNamesCollector::parse(cli.css_file)?;
 
     eprintln!("[INFO] extracted selectors");
-    println!("{:?}", c.class_names);
 
-    let mut ppc = ApplyTailwindPrefix::new(&cli.prefix, &c.class_names, &cli.scopes);
+    let mut ppc = ApplyTailwindPrefix::new(&cli.prefix, &c.class_names, &scopes);
+    ppc.dry_run = true;
+    ppc.explain = Some(Explain::default());
+
+    ppc.follow_imports_in_dirs(&[cli.context]);
+    ppc.prefix_classes_in_file(&cli.file)?;
 
-    let mut count = 0;
-    for context in &cli.contexts {
-        count += ppc.prefix_all_classes_in_dir(context)?;
+    ppc.explain
+        .expect("explain was set")
+        .write(std::io::stdout().lock())
+}
+
+fn undo(cli: UndoArgs) -> anyhow::Result<()> {
+    let root = Path::new(JOURNAL_DIR);
+
+    if cli.list {
+        for run in journal::runs(root)? {
+            println!("{run}");
+        }
+        return Ok(());
     }
 
+    let count = journal::undo(root, cli.run_id.as_deref(), cli.force)?;
+
     eprintln!(
         "{}",
-        format!("[DONE] {} files were tranformed.", count).green()
+        format!("[DONE] {} files were restored.", count).green()
     );
 
     Ok(())
@@ -141,7 +477,15 @@ mod tests {
         let cssfile = "fixtures/sample.css";
         let mut cmd = Command::cargo_bin("cnat").unwrap();
         let cmd = cmd
-            .args(["prefix", "-i", cssfile, "--prefix", "tw-", context_dir])
+            .args([
+                "prefix",
+                "--no-journal",
+                "-i",
+                cssfile,
+                "--prefix",
+                "tw-",
+                context_dir,
+            ])
             .assert()
             .success();
 
@@ -182,6 +526,7 @@ mod tests {
         let cmd = cmd
             .args([
                 "prefix",
+                "--no-journal",
                 "-i",
                 cssfile,
                 "--prefix",
@@ -232,6 +577,7 @@ mod tests {
         let cmd = cmd
             .args([
                 "prefix",
+                "--no-journal",
                 "-i",
                 cssfile,
                 "--prefix",
@@ -283,6 +629,7 @@ mod tests {
         let cmd = cmd
             .args([
                 "prefix",
+                "--no-journal",
                 "-i",
                 cssfile,
                 "--prefix",
@@ -318,14 +665,30 @@ mod tests {
     }
 
     #[test]
-    fn it_preserves_comments() {
-        let context_dir = "preserves_comments";
-        let jsfile = JsFile::prep("fixtures/sample_comments.jsx", context_dir);
+    fn it_reports_replacements_as_json() {
+        let context_dir = "report_json";
+        let _jsfiles = [
+            JsFile::prep("fixtures/sample.tsx", context_dir),
+            JsFile::prep("fixtures/sample2.tsx", context_dir),
+        ];
 
         let cssfile = "fixtures/sample.css";
+        let scopes = "att:className fn:cva";
         let mut cmd = Command::cargo_bin("cnat").unwrap();
         let cmd = cmd
-            .args(["prefix", "-i", cssfile, "--prefix", "tw-", context_dir])
+            .args([
+                "prefix",
+                "--no-journal",
+                "-i",
+                cssfile,
+                "--prefix",
+                "tw-",
+                context_dir,
+                "--scopes",
+                scopes,
+                "--report",
+                "json",
+            ])
             .assert()
             .success();
 
@@ -335,29 +698,988 @@ mod tests {
 
         insta::with_settings!({
             info => &cssfile,
+            description => scopes,
             omit_expression => true
         }, {
             assert_snapshot!(output);
         });
+    }
+
+    #[test]
+    fn it_reports_findings_as_sarif_in_check_mode() {
+        let context_dir = "check_sarif";
+        let jsfile = JsFile::prep("fixtures/nested/sample.tsx", context_dir);
+
+        let cssfile = "fixtures/sample.css";
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        let cmd = cmd
+            .args([
+                "prefix",
+                "--no-journal",
+                "-i",
+                cssfile,
+                "--prefix",
+                "tw-",
+                context_dir,
+                "--check",
+                "--report",
+                "sarif",
+            ])
+            .assert()
+            .failure();
+
+        let output = cmd.get_output();
+
+        let output = String::from_utf8_lossy(&output.stdout);
 
         insta::with_settings!({
-            snapshot_suffix => jsfile.0.to_string_lossy(),
-            info => &jsfile.0,
+            info => &cssfile,
             omit_expression => true
         }, {
-            assert_snapshot!(jsfile.content_now());
+            assert_snapshot!(output);
         });
+
+        assert_eq!(jsfile.content_now().as_bytes(), jsfile.1);
     }
 
     #[test]
-    fn it_leaves_alone_files_without_classes_to_prefix() {
-        let context_dir = "leave_unstyled";
-        let jsfile = JsFile::prep("fixtures/unstyled.tsx", context_dir);
+    fn it_audits_class_usage() {
+        let context_dir = "audit";
+        let jsfiles = [
+            JsFile::prep("fixtures/sample.tsx", context_dir),
+            JsFile::prep("fixtures/nested/sample.tsx", context_dir),
+            JsFile::prep("fixtures/sample2.tsx", context_dir),
+        ];
+
+        let cssfile = "fixtures/sample.css";
+        let scopes = "att:className,*ClassName prop:classes fn:cva";
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        let cmd = cmd
+            .args(["audit", "-i", cssfile, context_dir, "--scopes", scopes])
+            .assert()
+            .success();
+
+        let output = cmd.get_output();
+
+        let output = String::from_utf8_lossy(&output.stdout);
+
+        insta::with_settings!({
+            info => &cssfile,
+            description => scopes,
+            omit_expression => true
+        }, {
+            assert_snapshot!(output);
+        });
+
+        for jsfile in jsfiles {
+            assert_eq!(jsfile.content_now().as_bytes(), jsfile.1);
+        }
+    }
+
+    #[test]
+    fn it_reports_unused_classes() {
+        let context_dir = "audit_unused";
+        let _jsfile = JsFile::prep("fixtures/sample.tsx", context_dir);
+
+        let cssfile = "fixtures/layers.css";
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        let cmd = cmd
+            .args(["audit", "-i", cssfile, context_dir, "--unused"])
+            .assert()
+            .success();
+
+        let output = cmd.get_output();
+
+        let output = String::from_utf8_lossy(&output.stdout);
+
+        insta::with_settings!({
+            info => &cssfile,
+            omit_expression => true
+        }, {
+            assert_snapshot!(output);
+        });
+    }
+
+    #[test]
+    fn it_warns_about_unknown_classes() {
+        let context_dir = "unknown_classes";
+        let _jsfile = JsFile::prep("fixtures/typos.tsx", context_dir);
+
+        let cssfile = "fixtures/sample.css";
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        let cmd = cmd
+            .args([
+                "prefix",
+                "--no-journal",
+                "-i",
+                cssfile,
+                "--prefix",
+                "tw-",
+                context_dir,
+                "--unknown-classes",
+                "--allow-unknown",
+                "group,peer*",
+                "--report",
+                "checkstyle",
+            ])
+            .assert()
+            .success();
+
+        let output = cmd.get_output();
+
+        let output = String::from_utf8_lossy(&output.stdout);
+
+        insta::with_settings!({
+            info => &cssfile,
+            omit_expression => true
+        }, {
+            assert_snapshot!(output);
+        });
+    }
+
+    #[test]
+    fn it_warns_about_dynamic_class_names() {
+        let context_dir = "dynamic_classes";
+        let _jsfile = JsFile::prep("fixtures/dynamic.tsx", context_dir);
 
         let cssfile = "fixtures/sample.css";
+        let scopes = "att:className fn:cn";
         let mut cmd = Command::cargo_bin("cnat").unwrap();
         let cmd = cmd
-            .args(["prefix", "-i", cssfile, "--prefix", "tw-", context_dir])
+            .args([
+                "prefix",
+                "--no-journal",
+                "-i",
+                cssfile,
+                "--prefix",
+                "tw-",
+                context_dir,
+                "--scopes",
+                scopes,
+                "--report",
+                "checkstyle",
+            ])
+            .assert()
+            .success();
+
+        let output = cmd.get_output();
+
+        let output = String::from_utf8_lossy(&output.stdout);
+
+        insta::with_settings!({
+            info => &cssfile,
+            description => scopes,
+            omit_expression => true
+        }, {
+            assert_snapshot!(output);
+        });
+    }
+
+    #[test]
+    fn it_follows_const_bindings_used_in_scope() {
+        let context_dir = "bindings";
+        let jsfile = JsFile::prep("fixtures/bindings.tsx", context_dir);
+
+        let cssfile = "fixtures/sample.css";
+        let scopes = "att:className fn:cn";
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        cmd.args([
+            "prefix",
+            "--no-journal",
+            "-i",
+            cssfile,
+            "--prefix",
+            "tw-",
+            context_dir,
+            "--scopes",
+            scopes,
+        ])
+        .assert()
+        .success();
+
+        insta::with_settings!({
+            snapshot_suffix => jsfile.0.to_string_lossy(),
+            info => &jsfile.0,
+            description => scopes,
+            omit_expression => true
+        }, {
+            assert_snapshot!(jsfile.content_now());
+        });
+    }
+
+    #[test]
+    fn it_follows_class_constants_imported_in_scope() {
+        let context_dir = "modules";
+        let jsfiles = [
+            JsFile::prep("fixtures/modules/button.tsx", context_dir),
+            JsFile::prep("fixtures/modules/styles.ts", context_dir),
+        ];
+
+        let cssfile = "fixtures/sample.css";
+        let scopes = "att:className fn:cn";
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        cmd.args([
+            "prefix",
+            "--no-journal",
+            "-i",
+            cssfile,
+            "--prefix",
+            "tw-",
+            context_dir,
+            "--scopes",
+            scopes,
+        ])
+        .assert()
+        .success();
+
+        for jsfile in jsfiles {
+            insta::with_settings!({
+                snapshot_suffix => jsfile.0.to_string_lossy(),
+                info => &jsfile.0,
+                description => scopes,
+                omit_expression => true
+            }, {
+                assert_snapshot!(jsfile.content_now());
+            });
+        }
+    }
+
+    #[test]
+    fn it_goes_on_when_the_tsconfig_is_invalid() {
+        let context_dir = "invalid_tsconfig";
+        let jsfile = JsFile::prep("fixtures/sample.tsx", context_dir);
+        let tsconfig = format!("{context_dir}/tsconfig.json");
+        fs::write(&tsconfig, "{ \"compilerOptions\": ").unwrap();
+
+        let cssfile = "fixtures/sample.css";
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        let output = cmd
+            .args([
+                "prefix",
+                "--no-journal",
+                "-i",
+                cssfile,
+                "--prefix",
+                "tw-",
+                context_dir,
+            ])
+            .output()
+            .unwrap();
+
+        fs::remove_file(&tsconfig).unwrap();
+
+        assert!(output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("failed to parse"));
+        assert_ne!(jsfile.content_now().as_bytes(), jsfile.1);
+    }
+
+    #[test]
+    fn it_follows_bindings_of_each_file_on_its_own() {
+        let context_dir = "isolated";
+        let a = JsFile::prep("fixtures/isolated/a.tsx", context_dir);
+        let b = JsFile::prep("fixtures/isolated/b.tsx", context_dir);
+
+        let cssfile = "fixtures/sample.css";
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        cmd.args([
+            "prefix",
+            "--no-journal",
+            "-i",
+            cssfile,
+            "--prefix",
+            "tw-",
+            context_dir,
+        ])
+        .assert()
+        .success();
+
+        assert!(a.content_now().contains(r#"["tw-px-2", extra]"#));
+        // `extra` is referenced in scope in a.tsx, it's another binding in b.tsx
+        assert_eq!(b.content_now().as_bytes(), b.1);
+    }
+
+    #[test]
+    fn it_leaves_exports_used_out_of_scope_alone() {
+        let context_dir = "shared";
+        let styles = JsFile::prep("fixtures/shared/styles.ts", context_dir);
+        let a = JsFile::prep("fixtures/shared/a.tsx", context_dir);
+        let b = JsFile::prep("fixtures/shared/b.tsx", context_dir);
+
+        let cssfile = "fixtures/sample.css";
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        cmd.args([
+            "prefix",
+            "--no-journal",
+            "-i",
+            cssfile,
+            "--prefix",
+            "tw-",
+            context_dir,
+        ])
+        .assert()
+        .success();
+
+        // `label` is in scope in a.tsx, but b.tsx uses it as a placeholder
+        assert_eq!(styles.content_now().as_bytes(), styles.1);
+        assert_eq!(a.content_now().as_bytes(), a.1);
+        assert_eq!(b.content_now().as_bytes(), b.1);
+    }
+
+    #[test]
+    fn it_follows_imports_across_contexts() {
+        let context_dir = "contexts";
+        let page = JsFile::prep("fixtures/contexts/app/page.tsx", context_dir);
+        let styles = JsFile::prep("fixtures/contexts/ui/styles.ts", context_dir);
+
+        let cssfile = "fixtures/sample.css";
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        cmd.args([
+            "prefix",
+            "--no-journal",
+            "-i",
+            cssfile,
+            "--prefix",
+            "tw-",
+            "contexts/fixtures/contexts/app",
+            "contexts/fixtures/contexts/ui",
+        ])
+        .assert()
+        .success();
+
+        assert_eq!(page.content_now().as_bytes(), page.1);
+        assert_eq!(
+            styles.content_now(),
+            "export const buttonStyles = \"tw-px-2 tw-py-1\";\n"
+        );
+    }
+
+    #[test]
+    fn it_only_prefixes_selected_fn_args() {
+        let context_dir = "arg_selectors";
+        let jsfile = JsFile::prep("fixtures/variants.tsx", context_dir);
+
+        let cssfile = "fixtures/sample.css";
+        let scopes = "fn:cva[0],cva[1].variants.*.*,cva[1].compoundVariants[*].class";
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        cmd.args([
+            "prefix",
+            "--no-journal",
+            "-i",
+            cssfile,
+            "--prefix",
+            "tw-",
+            context_dir,
+            "--scopes",
+            scopes,
+        ])
+        .assert()
+        .success();
+
+        insta::with_settings!({
+            snapshot_suffix => jsfile.0.to_string_lossy(),
+            info => &jsfile.0,
+            description => scopes,
+            omit_expression => true
+        }, {
+            assert_snapshot!(jsfile.content_now());
+        });
+    }
+
+    #[test]
+    fn it_leaves_excluded_regions_alone() {
+        let context_dir = "exclusions";
+        let jsfile = JsFile::prep("fixtures/exclusions.tsx", context_dir);
+
+        let cssfile = "fixtures/sample.css";
+        let scopes = "att:className fn:cn,createElement !fn:t !prop:testId";
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        cmd.args([
+            "prefix",
+            "--no-journal",
+            "-i",
+            cssfile,
+            "--prefix",
+            "tw-",
+            context_dir,
+            "--scopes",
+            scopes,
+        ])
+        .assert()
+        .success();
+
+        insta::with_settings!({
+            snapshot_suffix => jsfile.0.to_string_lossy(),
+            info => &jsfile.0,
+            description => scopes,
+            omit_expression => true
+        }, {
+            assert_snapshot!(jsfile.content_now());
+        });
+    }
+
+    #[test]
+    fn it_matches_string_keys() {
+        let context_dir = "string_keys";
+        let jsfile = JsFile::prep("fixtures/keys.tsx", context_dir);
+
+        let cssfile = "fixtures/sample.css";
+        let scopes = "att:className,classList fn:clsx prop:className";
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        cmd.args([
+            "prefix",
+            "--no-journal",
+            "-i",
+            cssfile,
+            "--prefix",
+            "tw-",
+            context_dir,
+            "--scopes",
+            scopes,
+        ])
+        .assert()
+        .success();
+
+        insta::with_settings!({
+            snapshot_suffix => jsfile.0.to_string_lossy(),
+            info => &jsfile.0,
+            description => scopes,
+            omit_expression => true
+        }, {
+            assert_snapshot!(jsfile.content_now());
+        });
+    }
+
+    #[test]
+    fn it_matches_attributes_of_specific_elements() {
+        let context_dir = "element_scopes";
+        let jsfile = JsFile::prep("fixtures/elements.tsx", context_dir);
+
+        let cssfile = "fixtures/sample.css";
+        let scopes = "att:className,Icon.color,Dialog.Panel.*ClassName el:Tooltip !att:text";
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        cmd.args([
+            "prefix",
+            "--no-journal",
+            "-i",
+            cssfile,
+            "--prefix",
+            "tw-",
+            context_dir,
+            "--scopes",
+            scopes,
+        ])
+        .assert()
+        .success();
+
+        insta::with_settings!({
+            snapshot_suffix => jsfile.0.to_string_lossy(),
+            info => &jsfile.0,
+            description => scopes,
+            omit_expression => true
+        }, {
+            assert_snapshot!(jsfile.content_now());
+        });
+    }
+
+    #[test]
+    fn it_matches_assignments_and_method_calls() {
+        let context_dir = "dom_scopes";
+        let jsfile = JsFile::prep("fixtures/dom.ts", context_dir);
+
+        let cssfile = "fixtures/sample.css";
+        let scopes = "assign:className fn:classList.*,add";
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        cmd.args([
+            "prefix",
+            "--no-journal",
+            "-i",
+            cssfile,
+            "--prefix",
+            "tw-",
+            context_dir,
+            "--scopes",
+            scopes,
+        ])
+        .assert()
+        .success();
+
+        insta::with_settings!({
+            snapshot_suffix => jsfile.0.to_string_lossy(),
+            info => &jsfile.0,
+            description => scopes,
+            omit_expression => true
+        }, {
+            assert_snapshot!(jsfile.content_now());
+        });
+    }
+
+    #[test]
+    fn it_prefixes_classes_in_selectors() {
+        let context_dir = "selector_scopes";
+        let jsfile = JsFile::prep("fixtures/selectors.ts", context_dir);
+
+        let cssfile = "fixtures/sample.css";
+        let scopes = "sel:querySelector,querySelectorAll,locator,closest fn:classList.add";
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        cmd.args([
+            "prefix",
+            "--no-journal",
+            "-i",
+            cssfile,
+            "--prefix",
+            "tw-",
+            context_dir,
+            "--scopes",
+            scopes,
+        ])
+        .assert()
+        .success();
+
+        insta::with_settings!({
+            snapshot_suffix => jsfile.0.to_string_lossy(),
+            info => &jsfile.0,
+            description => scopes,
+            omit_expression => true
+        }, {
+            assert_snapshot!(jsfile.content_now());
+        });
+    }
+
+    #[test]
+    fn it_prefixes_literal_types_and_enums() {
+        let context_dir = "type_scopes";
+        let jsfile = JsFile::prep("fixtures/types.ts", context_dir);
+
+        let cssfile = "fixtures/sample.css";
+        let scopes = "type:Gap enum:Tone";
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        cmd.args([
+            "prefix",
+            "--no-journal",
+            "-i",
+            cssfile,
+            "--prefix",
+            "tw-",
+            context_dir,
+            "--scopes",
+            scopes,
+        ])
+        .assert()
+        .success();
+
+        insta::with_settings!({
+            snapshot_suffix => jsfile.0.to_string_lossy(),
+            info => &jsfile.0,
+            description => scopes,
+            omit_expression => true
+        }, {
+            assert_snapshot!(jsfile.content_now());
+        });
+    }
+
+    #[test]
+    fn it_merges_presets_with_scopes() {
+        let context_dir = "presets";
+        let jsfile = JsFile::prep("fixtures/variants.tsx", context_dir);
+
+        let cssfile = "fixtures/sample.css";
+        let presets = "shadcn,clsx";
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        cmd.args([
+            "prefix",
+            "--no-journal",
+            "-i",
+            cssfile,
+            "--prefix",
+            "tw-",
+            context_dir,
+            "--preset",
+            presets,
+        ])
+        .assert()
+        .success();
+
+        insta::with_settings!({
+            snapshot_suffix => jsfile.0.to_string_lossy(),
+            info => &jsfile.0,
+            description => presets,
+            omit_expression => true
+        }, {
+            assert_snapshot!(jsfile.content_now());
+        });
+    }
+
+    #[test]
+    fn it_lists_presets() {
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        let output = cmd.args(["scopes", "list-presets"]).output().unwrap();
+
+        assert!(output.status.success());
+        assert_snapshot!(String::from_utf8_lossy(&output.stdout));
+    }
+
+    #[test]
+    fn it_suggests_scopes_for_classes_out_of_scope() {
+        let context_dir = "suggest";
+        let _jsfile = JsFile::prep("fixtures/suggest.tsx", context_dir);
+
+        let cssfile = "fixtures/sample.css";
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        let output = cmd
+            .args(["scopes", "suggest", "-i", cssfile, context_dir])
+            .output()
+            .unwrap();
+
+        assert!(output.status.success());
+        assert_snapshot!(String::from_utf8_lossy(&output.stdout));
+    }
+
+    #[test]
+    fn it_explains_why_strings_were_transformed() {
+        let cssfile = "fixtures/sample.css";
+        let scopes = "att:className fn:cn !fn:t";
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        let output = cmd
+            .args([
+                "explain",
+                "-i",
+                cssfile,
+                "--prefix",
+                "tw-",
+                "fixtures/explain.tsx",
+                "--scopes",
+                scopes,
+                "--context",
+                "fixtures",
+            ])
+            .output()
+            .unwrap();
+
+        assert!(output.status.success());
+        insta::with_settings!({
+            description => scopes,
+            omit_expression => true
+        }, {
+            assert_snapshot!(String::from_utf8_lossy(&output.stdout));
+        });
+    }
+
+    #[test]
+    fn it_explains_bindings_imported_in_scope() {
+        let cssfile = "fixtures/sample.css";
+        let scopes = "att:className fn:cn";
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        let output = cmd
+            .args([
+                "explain",
+                "-i",
+                cssfile,
+                "fixtures/modules/styles.ts",
+                "--scopes",
+                scopes,
+                "--context",
+                "fixtures/modules",
+            ])
+            .output()
+            .unwrap();
+
+        assert!(output.status.success());
+        insta::with_settings!({
+            description => scopes,
+            omit_expression => true
+        }, {
+            assert_snapshot!(String::from_utf8_lossy(&output.stdout));
+        });
+    }
+
+    #[test]
+    fn it_follows_comment_directives() {
+        let context_dir = "directives";
+        let jsfile = JsFile::prep("fixtures/directives.tsx", context_dir);
+
+        let cssfile = "fixtures/sample.css";
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        cmd.args([
+            "prefix",
+            "--no-journal",
+            "-i",
+            cssfile,
+            "--prefix",
+            "tw-",
+            context_dir,
+        ])
+        .assert()
+        .success();
+
+        insta::with_settings!({
+            snapshot_suffix => jsfile.0.to_string_lossy(),
+            info => &jsfile.0,
+            omit_expression => true
+        }, {
+            assert_snapshot!(jsfile.content_now());
+        });
+    }
+
+    #[test]
+    fn it_skips_generated_files() {
+        let context_dir = "generated";
+        let jsfiles = [
+            JsFile::prep("fixtures/generated/schema.tsx", context_dir),
+            JsFile::prep("fixtures/generated/vendor.min.js", context_dir),
+        ];
+
+        let cssfile = "fixtures/sample.css";
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        let output = cmd
+            .args([
+                "prefix",
+                "--no-journal",
+                "-i",
+                cssfile,
+                "--prefix",
+                "tw-",
+                context_dir,
+                "-v",
+            ])
+            .output()
+            .unwrap();
+
+        assert!(output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        for jsfile in &jsfiles {
+            assert_eq!(jsfile.content_now().as_bytes(), jsfile.1);
+            assert!(stderr.contains(&format!("skipped {}", jsfile.0.display())));
+        }
+
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        cmd.args([
+            "prefix",
+            "--no-journal",
+            "-i",
+            cssfile,
+            "--prefix",
+            "tw-",
+            context_dir,
+            "--include-generated",
+        ])
+        .assert()
+        .success();
+
+        for jsfile in &jsfiles {
+            assert!(jsfile.content_now().contains("tw-px-2 tw-py-1"));
+        }
+    }
+
+    #[test]
+    fn it_keeps_escapes_as_written() {
+        let context_dir = "escapes";
+        let jsfile = JsFile::prep("fixtures/escapes.tsx", context_dir);
+
+        let cssfile = "fixtures/sample.css";
+        let scopes = "att:className var:label,spaced,continued sel:querySelector";
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        cmd.args([
+            "prefix",
+            "--no-journal",
+            "-i",
+            cssfile,
+            "--prefix",
+            "tw-",
+            context_dir,
+            "--scopes",
+            scopes,
+        ])
+        .assert()
+        .success();
+
+        insta::with_settings!({
+            snapshot_suffix => jsfile.0.to_string_lossy(),
+            info => &jsfile.0,
+            description => scopes,
+            omit_expression => true
+        }, {
+            assert_snapshot!(jsfile.content_now());
+        });
+    }
+
+    #[test]
+    fn it_splits_classes_on_any_whitespace() {
+        let context_dir = "whitespace";
+        let jsfile = JsFile::prep("fixtures/whitespace.tsx", context_dir);
+
+        let cssfile = "fixtures/sample.css";
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        cmd.args([
+            "prefix",
+            "--no-journal",
+            "-i",
+            cssfile,
+            "--prefix",
+            "tw-",
+            context_dir,
+        ])
+        .assert()
+        .success();
+
+        insta::with_settings!({
+            snapshot_suffix => jsfile.0.to_string_lossy(),
+            info => &jsfile.0,
+            omit_expression => true
+        }, {
+            assert_snapshot!(jsfile.content_now());
+        });
+    }
+
+    #[test]
+    fn it_undoes_a_run() {
+        // runs from the context directory, for its own journal
+        let context_dir = "undo";
+        let jsfile = JsFile::prep("fixtures/sample.tsx", context_dir);
+
+        let cssfile = "../fixtures/sample.css";
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        cmd.current_dir(context_dir)
+            .args(["prefix", "-i", cssfile, "--prefix", "tw-", "fixtures"])
+            .assert()
+            .success();
+
+        assert_ne!(jsfile.content_now().as_bytes(), jsfile.1);
+
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        cmd.current_dir(context_dir).arg("undo").assert().success();
+
+        assert_eq!(jsfile.content_now().as_bytes(), jsfile.1);
+
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        let output = cmd
+            .current_dir(context_dir)
+            .args(["undo", "--list"])
+            .output()
+            .unwrap();
+        assert!(output.stdout.is_empty());
+
+        fs::remove_dir_all(format!("{context_dir}/{}", super::JOURNAL_DIR)).unwrap();
+    }
+
+    #[test]
+    fn it_keeps_no_journal_when_asked() {
+        let context_dir = "no_journal";
+        let jsfile = JsFile::prep("fixtures/sample.tsx", context_dir);
+
+        let cssfile = "../fixtures/sample.css";
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        cmd.current_dir(context_dir)
+            .args([
+                "prefix",
+                "--no-journal",
+                "-i",
+                cssfile,
+                "--prefix",
+                "tw-",
+                "fixtures",
+            ])
+            .assert()
+            .success();
+
+        assert_ne!(jsfile.content_now().as_bytes(), jsfile.1);
+        assert!(!PathBuf::from(context_dir).join(super::JOURNAL_DIR).exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn it_writes_through_symlinks_and_undoes_them() {
+        let context_dir = "undo_symlink";
+        let jsfile = JsFile::prep("fixtures/sample.tsx", context_dir);
+        let link = PathBuf::from(format!("{context_dir}/linked/sample.tsx"));
+        fs::create_dir_all(link.parent().unwrap()).unwrap();
+        std::os::unix::fs::symlink("../fixtures/sample.tsx", &link).unwrap();
+
+        let cssfile = "../fixtures/sample.css";
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        cmd.current_dir(context_dir)
+            .args(["prefix", "-i", cssfile, "--prefix", "tw-", "linked"])
+            .assert()
+            .success();
+
+        assert!(link.is_symlink());
+        assert_ne!(jsfile.content_now().as_bytes(), jsfile.1);
+
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        cmd.current_dir(context_dir).arg("undo").assert().success();
+
+        assert!(link.is_symlink());
+        assert_eq!(jsfile.content_now().as_bytes(), jsfile.1);
+
+        // every file was restored, so the run is gone
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        let output = cmd
+            .current_dir(context_dir)
+            .args(["undo", "--list"])
+            .output()
+            .unwrap();
+        assert!(output.stdout.is_empty());
+
+        fs::remove_file(&link).unwrap();
+        fs::remove_dir_all(format!("{context_dir}/{}", super::JOURNAL_DIR)).unwrap();
+    }
+
+    #[test]
+    fn it_preserves_comments() {
+        let context_dir = "preserves_comments";
+        let jsfile = JsFile::prep("fixtures/sample_comments.jsx", context_dir);
+
+        let cssfile = "fixtures/sample.css";
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        let cmd = cmd
+            .args([
+                "prefix",
+                "--no-journal",
+                "-i",
+                cssfile,
+                "--prefix",
+                "tw-",
+                context_dir,
+            ])
+            .assert()
+            .success();
+
+        let output = cmd.get_output();
+
+        let output = String::from_utf8_lossy(&output.stdout);
+
+        insta::with_settings!({
+            info => &cssfile,
+            omit_expression => true
+        }, {
+            assert_snapshot!(output);
+        });
+
+        insta::with_settings!({
+            snapshot_suffix => jsfile.0.to_string_lossy(),
+            info => &jsfile.0,
+            omit_expression => true
+        }, {
+            assert_snapshot!(jsfile.content_now());
+        });
+    }
+
+    #[test]
+    fn it_leaves_alone_files_without_classes_to_prefix() {
+        let context_dir = "leave_unstyled";
+        let jsfile = JsFile::prep("fixtures/unstyled.tsx", context_dir);
+
+        let cssfile = "fixtures/sample.css";
+        let mut cmd = Command::cargo_bin("cnat").unwrap();
+        let cmd = cmd
+            .args([
+                "prefix",
+                "--no-journal",
+                "-i",
+                cssfile,
+                "--prefix",
+                "tw-",
+                context_dir,
+            ])
             .assert()
             .success();
 
@@ -0,0 +1,286 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Context};
+
+const EXTENSIONS: [&str; 4] = ["ts", "tsx", "js", "jsx"];
+
+/// (pattern, substitutions), e.g. ("@/*", ["./src/*"])
+type Paths = Vec<(String, Vec<String>)>;
+
+/// Resolves import specifiers to the js/ts files they refer to, relative ones and the ones
+/// mapped by the `baseUrl` and `paths` of the closest tsconfig.json.
+#[derive(Default)]
+pub struct ModuleResolver {
+    base_url: Option<PathBuf>,
+    /// The directory `paths` are relative to.
+    paths_root: PathBuf,
+    paths: Paths,
+}
+
+impl ModuleResolver {
+    /// Uses the tsconfig.json in `dir` or in the closest of its ancestors, if any,
+    /// with the configs it extends.
+    pub fn new(dir: &Path) -> anyhow::Result<Self> {
+        let dir = dir.canonicalize()?;
+        let Some(tsconfig) = dir
+            .ancestors()
+            .map(|d| d.join("tsconfig.json"))
+            .find(|p| p.is_file())
+        else {
+            return Ok(Self::default());
+        };
+
+        let options = Options::load(&tsconfig, 0)?;
+        let (paths_dir, paths) = options.paths.unwrap_or_default();
+
+        Ok(Self {
+            // paths are relative to the tsconfig that has them when there's no baseUrl
+            paths_root: options.base_url.clone().unwrap_or(paths_dir),
+            base_url: options.base_url,
+            paths,
+        })
+    }
+
+    pub fn resolve(&self, from: &Path, specifier: &str) -> Option<PathBuf> {
+        if specifier.starts_with("./") || specifier.starts_with("../") {
+            let dir = from.parent()?;
+            return resolve_file(&dir.join(specifier));
+        }
+
+        for (pattern, substitutions) in &self.paths {
+            let Some(matched) = match_path_pattern(pattern, specifier) else {
+                continue;
+            };
+
+            for substitution in substitutions {
+                let path = self.paths_root.join(substitution.replacen('*', matched, 1));
+                if let Some(file) = resolve_file(&path) {
+                    return Some(file);
+                }
+            }
+        }
+
+        resolve_file(&self.base_url.as_ref()?.join(specifier))
+    }
+}
+
+/// How many configs deep `extends` is followed, in case of a cycle.
+const MAX_EXTENDS_DEPTH: usize = 16;
+
+/// The `compilerOptions` used to resolve imports, from a tsconfig and the configs it extends.
+#[derive(Default)]
+struct Options {
+    base_url: Option<PathBuf>,
+    /// (the directory of the tsconfig that has them, paths)
+    paths: Option<(PathBuf, Paths)>,
+}
+
+impl Options {
+    fn load(tsconfig: &Path, depth: usize) -> anyhow::Result<Self> {
+        if depth > MAX_EXTENDS_DEPTH {
+            bail!("too many nested extends in {}", tsconfig.display());
+        }
+
+        let contents = std::fs::read_to_string(tsconfig)
+            .with_context(|| format!("failed to read {}", tsconfig.display()))?;
+        let config: serde_json::Value = serde_json::from_str(&strip_jsonc(&contents))
+            .with_context(|| format!("failed to parse {}", tsconfig.display()))?;
+
+        let root = tsconfig.parent().expect("a file should have a parent");
+
+        // a string, or an array since typescript 5, where later configs override earlier ones
+        let extends = match &config["extends"] {
+            serde_json::Value::String(base) => vec![base.as_str()],
+            serde_json::Value::Array(bases) => bases.iter().filter_map(|b| b.as_str()).collect(),
+            _ => vec![],
+        };
+
+        let mut options = Self::default();
+        for base in extends {
+            let path = resolve_extends(root, base).ok_or_else(|| {
+                anyhow!("can't find {base:?}, extended by {}", tsconfig.display())
+            })?;
+            let base = Self::load(&path, depth + 1)?;
+            options.base_url = base.base_url.or(options.base_url);
+            options.paths = base.paths.or(options.paths);
+        }
+
+        let compiler_options = &config["compilerOptions"];
+        if let Some(url) = compiler_options["baseUrl"].as_str() {
+            options.base_url = Some(root.join(url));
+        }
+
+        if let Some(paths) = compiler_options["paths"].as_object() {
+            let paths = paths
+                .iter()
+                .map(|(pattern, substitutions)| {
+                    let substitutions = substitutions
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|s| s.as_str().map(String::from))
+                        .collect();
+                    (pattern.clone(), substitutions)
+                })
+                .collect();
+            options.paths = Some((root.to_path_buf(), paths));
+        }
+
+        Ok(options)
+    }
+}
+
+/// The config an `extends` refers to: a path relative to the extending config,
+/// or a package in `node_modules`, e.g. `@tsconfig/next/tsconfig.json` or `@tsconfig/next`.
+fn resolve_extends(root: &Path, base: &str) -> Option<PathBuf> {
+    let with_json = |path: PathBuf| {
+        if path.is_file() {
+            return Some(path);
+        }
+        let mut json = path.into_os_string();
+        json.push(".json");
+        Some(PathBuf::from(json)).filter(|p| p.is_file())
+    };
+
+    if base.starts_with('.') || Path::new(base).is_absolute() {
+        return with_json(root.join(base));
+    }
+
+    root.ancestors()
+        .map(|dir| dir.join("node_modules").join(base))
+        .find_map(|path| with_json(path.clone()).or_else(|| with_json(path.join("tsconfig"))))
+}
+
+fn resolve_file(path: &Path) -> Option<PathBuf> {
+    let candidates = std::iter::once(path.to_path_buf())
+        .chain(EXTENSIONS.iter().map(|ext| {
+            let mut p = path.as_os_str().to_owned();
+            p.push(".");
+            p.push(ext);
+            PathBuf::from(p)
+        }))
+        .chain(
+            EXTENSIONS
+                .iter()
+                .map(|ext| path.join("index").with_extension(ext)),
+        );
+
+    for candidate in candidates {
+        let is_source_file = candidate
+            .extension()
+            .is_some_and(|e| EXTENSIONS.iter().any(|ext| e == *ext));
+
+        if is_source_file && candidate.is_file() {
+            return candidate.canonicalize().ok();
+        }
+    }
+
+    None
+}
+
+/// Returns what the `*` in `pattern` matched, or "" when there's no wildcard and it matched exactly.
+fn match_path_pattern<'s>(pattern: &str, specifier: &'s str) -> Option<&'s str> {
+    match pattern.split_once('*') {
+        Some((prefix, suffix)) => specifier
+            .strip_prefix(prefix)?
+            .strip_suffix(suffix)
+            .filter(|m| m.len() + prefix.len() + suffix.len() == specifier.len()),
+        None => (pattern == specifier).then_some(""),
+    }
+}
+
+/// Removes the comments and trailing commas that tsconfig files are allowed to have.
+fn strip_jsonc(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => out.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                out.push(c);
+            }
+            ('/', Some('/')) => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        out.push(c);
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut prev = ' ';
+                for c in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+            }
+            (',', _) => {
+                let rest = chars.clone().find(|c| !c.is_whitespace());
+                if !matches!(rest, Some('}') | Some(']')) {
+                    out.push(c);
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_strips_jsonc() {
+        let jsonc = r#"{
+            // paths
+            "compilerOptions": { /* base */ "baseUrl": ".", "paths": { "@/*": ["./src/*",], }, },
+            "url": "http://example.com/*",
+        }"#;
+
+        let value: serde_json::Value = serde_json::from_str(&strip_jsonc(jsonc)).unwrap();
+
+        assert_eq!(value["compilerOptions"]["baseUrl"], ".");
+        assert_eq!(value["compilerOptions"]["paths"]["@/*"][0], "./src/*");
+        assert_eq!(value["url"], "http://example.com/*");
+    }
+
+    #[test]
+    fn it_follows_extends() {
+        let resolver = ModuleResolver::new(Path::new("fixtures/extends")).unwrap();
+
+        assert_eq!(
+            resolver.resolve(Path::new("fixtures/extends/app.tsx"), "@ui/styles"),
+            Path::new("fixtures/extends/ui/styles.ts")
+                .canonicalize()
+                .ok()
+        );
+    }
+
+    #[test]
+    fn it_matches_path_patterns() {
+        assert_eq!(
+            match_path_pattern("@/*", "@/components/button"),
+            Some("components/button")
+        );
+        assert_eq!(match_path_pattern("@styles", "@styles"), Some(""));
+        assert_eq!(match_path_pattern("@styles", "@styles/button"), None);
+        assert_eq!(match_path_pattern("@/*", "react"), None);
+    }
+}
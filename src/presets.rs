@@ -0,0 +1,96 @@
+use std::io::Write;
+use std::str::FromStr;
+
+use clap::ValueEnum;
+use cnat::scope::Scope;
+
+const CVA: &[&str] = &[
+    "fn:cva[0],cva[1].variants.*.*,cva[1].compoundVariants[*].class,cva[1].compoundVariants[*].className",
+];
+
+const CLSX: &[&str] = &["fn:clsx,cn,cx,classnames,classNames,twMerge,twJoin"];
+
+/// Curated scopes for popular libraries.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Preset {
+    /// shadcn/ui components, with `cn` and `cva`
+    Shadcn,
+    /// Headless UI, with the class props of `Transition`
+    Headlessui,
+    /// tailwind-variants
+    Tv,
+    /// clsx, classnames, and tailwind-merge
+    Clsx,
+    /// Material UI, with `classes` and `*ClassName` props
+    Mui,
+    /// Solid, with `class` and `classList`
+    Solid,
+    /// twin.macro, with the `tw` prop
+    Twin,
+    /// class-variance-authority
+    Cva,
+}
+
+impl Preset {
+    pub fn scopes(&self) -> Vec<&'static str> {
+        match self {
+            Preset::Shadcn => [&["att:className", "fn:cn"], CVA].concat(),
+            Preset::Headlessui => {
+                vec!["att:className,enter,enterFrom,enterTo,leave,leaveFrom,leaveTo,entered"]
+            }
+            Preset::Tv => vec![
+                "fn:tv[0].base,tv[0].slots.*,tv[0].variants.*.*,tv[0].compoundVariants[*].class,tv[0].compoundVariants[*].className,tv[0].compoundSlots[*].class,tv[0].compoundSlots[*].className",
+            ],
+            Preset::Clsx => CLSX.to_vec(),
+            Preset::Mui => vec!["att:className,classes,*ClassName", "prop:className,*ClassName"],
+            Preset::Solid => vec!["att:class,classList"],
+            Preset::Twin => vec!["att:tw"],
+            Preset::Cva => CVA.to_vec(),
+        }
+    }
+
+    fn description(&self) -> String {
+        self.to_possible_value()
+            .and_then(|v| v.get_help().map(|help| help.to_string()))
+            .unwrap_or_default()
+    }
+}
+
+/// The given scopes, followed by the scopes of every one of the presets.
+pub fn merge(scopes: &[Scope], presets: &[Preset]) -> Vec<Scope> {
+    let preset_scopes = presets
+        .iter()
+        .flat_map(|preset| preset.scopes())
+        .map(|s| Scope::from_str(s).expect("the scopes of a preset should be valid"));
+
+    scopes.iter().cloned().chain(preset_scopes).collect()
+}
+
+pub fn list(mut out: impl Write) -> anyhow::Result<()> {
+    for preset in Preset::value_variants() {
+        let name = preset
+            .to_possible_value()
+            .expect("no preset is skipped")
+            .get_name()
+            .to_string();
+
+        writeln!(out, "{name}: {}", preset.description())?;
+        writeln!(out, "  --scopes '{}'", preset.scopes().join(" "))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_every_preset() {
+        for preset in Preset::value_variants() {
+            for scope in preset.scopes() {
+                assert!(Scope::from_str(scope).is_ok(), "{preset:?}: {scope}");
+            }
+        }
+    }
+}
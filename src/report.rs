@@ -0,0 +1,361 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use serde::Serialize;
+use swc_common::{BytePos, SourceMap, Span};
+
+use cnat::scope::{ScopeValue, ScopeVariant};
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ReportFormat {
+    /// Every replacement made and every finding, plus a summary with per-class counts.
+    Json,
+    /// Findings as SARIF 2.1.0, for code scanning tools.
+    Sarif,
+    /// Findings as checkstyle XML.
+    Checkstyle,
+}
+
+/// The scope under which a string was found.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScopeEntry {
+    pub variant: String,
+    pub value: String,
+}
+
+impl ScopeEntry {
+    pub fn new(variant: ScopeVariant, value: &ScopeValue) -> Self {
+        Self {
+            variant: variant.to_string(),
+            value: value.to_string(),
+        }
+    }
+}
+
+/// A replacement whose position hasn't been resolved to a line and column yet.
+pub struct PendingReplacement {
+    pub pos: BytePos,
+    pub scope: ScopeEntry,
+    pub original: String,
+    pub new: String,
+    pub classes: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct ReplacementEntry {
+    pub file: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    pub scope: ScopeEntry,
+    pub original: String,
+    pub new: String,
+    /// The classes, without variants, that got prefixed in this string.
+    pub classes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+#[allow(clippy::enum_variant_names)] // named after their rule ids
+pub enum FindingKind {
+    /// A class from the css input that hasn't been prefixed yet.
+    LegacyClass,
+    /// A class, in scope, that isn't in the css input.
+    UnknownClass,
+    /// A class name, in scope, built from fragments at runtime.
+    DynamicClass,
+}
+
+impl FindingKind {
+    pub fn rule_id(&self) -> &'static str {
+        match self {
+            FindingKind::LegacyClass => "legacy-class",
+            FindingKind::UnknownClass => "unknown-class",
+            FindingKind::DynamicClass => "dynamic-class",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            FindingKind::LegacyClass => "A class from the css input is used without the prefix.",
+            FindingKind::UnknownClass => "A class is used that isn't defined in the css input.",
+            FindingKind::DynamicClass => {
+                "A class name is built at runtime, so it can't be prefixed, nor seen by tailwind."
+            }
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        match self {
+            FindingKind::LegacyClass => true,
+            FindingKind::UnknownClass | FindingKind::DynamicClass => false,
+        }
+    }
+
+    fn level(&self) -> &'static str {
+        if self.is_error() {
+            "error"
+        } else {
+            "warning"
+        }
+    }
+
+    const ALL: [FindingKind; 3] = [
+        FindingKind::LegacyClass,
+        FindingKind::UnknownClass,
+        FindingKind::DynamicClass,
+    ];
+}
+
+/// A finding whose span hasn't been resolved to lines and columns yet.
+pub struct PendingFinding {
+    pub kind: FindingKind,
+    pub span: Span,
+    pub message: String,
+}
+
+#[derive(Serialize)]
+pub struct Finding {
+    pub kind: FindingKind,
+    pub file: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for Finding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{}] {}:{}:{}: {} ({})",
+            self.kind.level().to_uppercase(),
+            self.file.display(),
+            self.line,
+            self.column,
+            self.message,
+            self.kind.rule_id()
+        )
+    }
+}
+
+#[derive(Serialize)]
+struct Summary {
+    files: usize,
+    replacements: usize,
+    classes: BTreeMap<String, usize>,
+}
+
+#[derive(Default)]
+pub struct Report {
+    replacements: Vec<ReplacementEntry>,
+    findings: Vec<Finding>,
+}
+
+impl Report {
+    pub fn record(
+        &mut self,
+        file: &Path,
+        cm: &SourceMap,
+        pending: impl IntoIterator<Item = PendingReplacement>,
+    ) {
+        let mut pending: Vec<_> = pending.into_iter().collect();
+        pending.sort_by_key(|p| p.pos);
+        pending.dedup_by_key(|p| p.pos);
+
+        for p in pending {
+            let loc = cm.lookup_char_pos(p.pos);
+            self.replacements.push(ReplacementEntry {
+                file: file.to_path_buf(),
+                line: loc.line,
+                column: loc.col.0 + 1,
+                scope: p.scope,
+                original: p.original,
+                new: p.new,
+                classes: p.classes,
+            });
+        }
+    }
+
+    pub fn record_findings(
+        &mut self,
+        file: &Path,
+        cm: &SourceMap,
+        pending: impl IntoIterator<Item = PendingFinding>,
+    ) {
+        let mut pending: Vec<_> = pending.into_iter().collect();
+        pending.sort_by_key(|p| p.span.lo);
+        pending.dedup_by(|a, b| a.kind == b.kind && a.span == b.span);
+
+        for p in pending {
+            let lo = cm.lookup_char_pos(p.span.lo);
+            let hi = cm.lookup_char_pos(p.span.hi);
+            self.findings.push(Finding {
+                kind: p.kind,
+                file: file.to_path_buf(),
+                line: lo.line,
+                column: lo.col.0 + 1,
+                end_line: hi.line,
+                end_column: hi.col.0 + 1,
+                message: p.message,
+            });
+        }
+    }
+
+    pub fn replacements(&self) -> &[ReplacementEntry] {
+        &self.replacements
+    }
+
+    pub fn findings(&self) -> &[Finding] {
+        &self.findings
+    }
+
+    fn summary(&self) -> Summary {
+        let mut files: Vec<_> = self.replacements.iter().map(|r| &r.file).collect();
+        files.dedup();
+
+        let mut classes = BTreeMap::new();
+        for class in self.replacements.iter().flat_map(|r| &r.classes) {
+            *classes.entry(class.clone()).or_default() += 1;
+        }
+
+        Summary {
+            files: files.len(),
+            replacements: self.replacements.len(),
+            classes,
+        }
+    }
+
+    pub fn write(&self, format: ReportFormat, mut out: impl Write) -> anyhow::Result<()> {
+        match format {
+            ReportFormat::Json => {
+                #[derive(Serialize)]
+                struct JsonReport<'r> {
+                    replacements: &'r [ReplacementEntry],
+                    findings: &'r [Finding],
+                    summary: Summary,
+                }
+
+                serde_json::to_writer_pretty(
+                    &mut out,
+                    &JsonReport {
+                        replacements: &self.replacements,
+                        findings: &self.findings,
+                        summary: self.summary(),
+                    },
+                )?;
+                writeln!(out)?;
+            }
+            ReportFormat::Sarif => {
+                serde_json::to_writer_pretty(&mut out, &self.sarif())?;
+                writeln!(out)?;
+            }
+            ReportFormat::Checkstyle => self.write_checkstyle(&mut out)?,
+        }
+
+        Ok(())
+    }
+
+    fn sarif(&self) -> serde_json::Value {
+        let rules: Vec<_> = FindingKind::ALL
+            .iter()
+            .map(|kind| {
+                serde_json::json!({
+                    "id": kind.rule_id(),
+                    "shortDescription": { "text": kind.description() },
+                    "defaultConfiguration": { "level": kind.level() },
+                })
+            })
+            .collect();
+
+        let results: Vec<_> = self
+            .findings
+            .iter()
+            .map(|f| {
+                serde_json::json!({
+                    "ruleId": f.kind.rule_id(),
+                    "level": f.kind.level(),
+                    "message": { "text": f.message },
+                    "locations": [{
+                        "physicalLocation": {
+                            "artifactLocation": { "uri": uri(&f.file) },
+                            "region": {
+                                "startLine": f.line,
+                                "startColumn": f.column,
+                                "endLine": f.end_line,
+                                "endColumn": f.end_column,
+                            }
+                        }
+                    }],
+                })
+            })
+            .collect();
+
+        serde_json::json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": clap::crate_name!(),
+                        "version": clap::crate_version!(),
+                        "informationUri": env!("CARGO_PKG_HOMEPAGE"),
+                        "rules": rules,
+                    }
+                },
+                "results": results,
+            }]
+        })
+    }
+
+    fn write_checkstyle(&self, mut out: impl Write) -> anyhow::Result<()> {
+        writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(out, r#"<checkstyle version="4.3">"#)?;
+
+        let mut findings = self.findings.iter().peekable();
+        while let Some(first) = findings.peek() {
+            let file = &first.file;
+            writeln!(out, r#"  <file name="{}">"#, xml_escape(&uri(file)))?;
+
+            while let Some(f) = findings.next_if(|f| &f.file == file) {
+                writeln!(
+                    out,
+                    r#"    <error line="{}" column="{}" severity="{}" message="{}" source="cnat.{}"/>"#,
+                    f.line,
+                    f.column,
+                    f.kind.level(),
+                    xml_escape(&f.message),
+                    f.kind.rule_id()
+                )?;
+            }
+
+            writeln!(out, "  </file>")?;
+        }
+
+        writeln!(out, "</checkstyle>")?;
+
+        Ok(())
+    }
+}
+
+fn uri(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+fn xml_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' => escaped.push_str("&#10;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
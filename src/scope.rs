@@ -5,75 +5,370 @@ use colored::Colorize;
 
 use crate::{Array, Str};
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 pub enum ScopeVariant {
     AttrNames,
     RecordEntries,
     FnCall,
+    Elements,
+    Assignments,
+    Selectors,
+    Types,
+    Enums,
+    Variables,
 }
 
-#[derive(Debug, Default, PartialEq, Clone, Copy)]
+#[derive(Debug, Default, PartialEq, Clone)]
 pub enum MatchType {
     #[default]
     Is,
     Contains,
     StartsWith,
     EndWith,
+    /// Wildcards anywhere, e.g. `data-*-class`.
+    Glob(Pattern),
+    /// e.g. `/^(icon|label)ClassNames?$/`
+    Regex(Pattern),
+}
+
+/// A compiled glob or regex value.
+#[derive(Debug, Clone)]
+pub struct Pattern(regex::Regex);
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
+/// Compiles a glob, where a `*` matches anything, to a regex that matches whole strings.
+pub fn glob_regex(glob: &str) -> Result<regex::Regex, regex::Error> {
+    let glob = glob
+        .split('*')
+        .map(regex::escape)
+        .collect::<Vec<_>>()
+        .join(".*");
+    regex::Regex::new(&format!("^{glob}$"))
+}
+
+/// A step into an argument of a function call, e.g. `.variants`, `[1]` or `.*`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum PathSegment {
+    Key(Str),
+    Index(usize),
+    /// Any key of an object, or any element of an array.
+    Any,
+}
+
+/// Selects the parts of a function call's arguments that are in scope, e.g. `[1].variants.*.*`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ArgSelector {
+    pub index: usize,
+    pub path: Array<PathSegment>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Qualifier {
+    /// The parts of a function call's arguments that are in scope.
+    Args(ArgSelector),
+    /// The jsx element an attribute has to be on, e.g. `Icon` or `Dialog.Panel`.
+    Element(Str),
 }
 
 #[derive(Debug, PartialEq, Clone)]
-pub struct ScopeValue(MatchType, Str);
+pub struct ScopeValue(MatchType, Str, Option<Qualifier>);
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Scope {
+    /// Whether this scope takes what it matches out of scope, e.g. `!fn:t`.
+    exclude: bool,
     variant: ScopeVariant,
     values: Array<ScopeValue>,
 }
 
-impl Scope {
-    pub fn matches(&self, s: &str, s_variant: ScopeVariant) -> bool {
-        for value in self.values.iter() {
-            let matches = match value.0 {
-                MatchType::Contains => s.contains(&*value.1),
-                MatchType::Is => *value.1 == *s,
-                MatchType::StartsWith => s.starts_with(&*value.1),
-                MatchType::EndWith => s.ends_with(&*value.1),
-            };
-
-            if matches && self.variant == s_variant {
-                return true;
+impl std::fmt::Display for ScopeVariant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScopeVariant::AttrNames => write!(f, "att"),
+            ScopeVariant::RecordEntries => write!(f, "prop"),
+            ScopeVariant::FnCall => write!(f, "fn"),
+            ScopeVariant::Elements => write!(f, "el"),
+            ScopeVariant::Assignments => write!(f, "assign"),
+            ScopeVariant::Selectors => write!(f, "sel"),
+            ScopeVariant::Types => write!(f, "type"),
+            ScopeVariant::Enums => write!(f, "enum"),
+            ScopeVariant::Variables => write!(f, "var"),
+        }
+    }
+}
+
+impl std::fmt::Display for Scope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.exclude {
+            write!(f, "!")?;
+        }
+        write!(f, "{}:", self.variant)?;
+        for (i, value) in self.values.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{value}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for PathSegment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PathSegment::Key(key) => write!(f, ".{}", &**key),
+            PathSegment::Index(i) => write!(f, "[{i}]"),
+            PathSegment::Any => write!(f, ".*"),
+        }
+    }
+}
+
+impl std::fmt::Display for ArgSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}]", self.index)?;
+        for segment in self.path.iter() {
+            write!(f, "{segment}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for ScopeValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(Qualifier::Element(element)) = &self.2 {
+            write!(f, "{}.", &**element)?;
+        }
+
+        match self.0 {
+            MatchType::Is | MatchType::Glob(_) => write!(f, "{}", &*self.1),
+            MatchType::Contains => write!(f, "*{}*", &*self.1),
+            MatchType::StartsWith => write!(f, "{}*", &*self.1),
+            MatchType::EndWith => write!(f, "*{}", &*self.1),
+            MatchType::Regex(_) => write!(f, "/{}/", &*self.1),
+        }?;
+
+        if let Some(Qualifier::Args(selector)) = &self.2 {
+            write!(f, "{selector}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl PathSegment {
+    pub fn matches_key(&self, key: &str) -> bool {
+        match self {
+            PathSegment::Key(k) => **k == *key,
+            PathSegment::Index(_) => false,
+            PathSegment::Any => true,
+        }
+    }
+
+    pub fn matches_index(&self, index: usize) -> bool {
+        match self {
+            PathSegment::Key(_) => false,
+            PathSegment::Index(i) => *i == index,
+            PathSegment::Any => true,
+        }
+    }
+}
+
+impl FromStr for ArgSelector {
+    type Err = anyhow::Error;
+
+    ///grammar -> [index](.key | .* | [index] | [*])*
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut segments = vec![];
+        let mut rest = s;
+
+        while !rest.is_empty() {
+            if let Some(r) = rest.strip_prefix('[') {
+                let (index, r) = r
+                    .split_once(']')
+                    .ok_or_else(|| anyhow!("unclosed '[' in selector: {s}"))?;
+                segments.push(match index {
+                    "*" => PathSegment::Any,
+                    _ => PathSegment::Index(
+                        index
+                            .parse()
+                            .with_context(|| format!("invalid index in selector: {s}"))?,
+                    ),
+                });
+                rest = r;
+            } else if let Some(r) = rest.strip_prefix('.') {
+                let end = r.find(['.', '[']).unwrap_or(r.len());
+                segments.push(match &r[..end] {
+                    "" => return Err(anyhow!("empty key in selector: {s}")),
+                    "*" => PathSegment::Any,
+                    key => PathSegment::Key(key.into()),
+                });
+                rest = &r[end..];
+            } else {
+                return Err(anyhow!("expected '.' or '[' in selector: {s}"));
             }
         }
 
-        false
+        let mut segments = segments.into_iter();
+        let Some(PathSegment::Index(index)) = segments.next() else {
+            return Err(anyhow!(
+                "a selector should start with an argument index, like [0]: {s}"
+            ));
+        };
+
+        Ok(ArgSelector {
+            index,
+            path: segments.collect(),
+        })
+    }
+}
+
+impl ScopeValue {
+    /// The parts of a function call's arguments that are in scope; all of them when `None`.
+    pub fn selector(&self) -> Option<&ArgSelector> {
+        match &self.2 {
+            Some(Qualifier::Args(selector)) => Some(selector),
+            _ => None,
+        }
+    }
+
+    /// Whether an attribute on the given jsx element can match this value.
+    /// [`ScopeValue::matches`] only checks the attribute's name.
+    pub fn matches_element(&self, element: Option<&str>) -> bool {
+        match &self.2 {
+            Some(Qualifier::Element(e)) => element == Some(&**e),
+            _ => true,
+        }
+    }
+
+    pub fn matches(&self, s: &str) -> bool {
+        match &self.0 {
+            MatchType::Contains => s.contains(&*self.1),
+            MatchType::Is => *self.1 == *s,
+            MatchType::StartsWith => s.starts_with(&*self.1),
+            MatchType::EndWith => s.ends_with(&*self.1),
+            MatchType::Glob(pattern) | MatchType::Regex(pattern) => pattern.0.is_match(s),
+        }
+    }
+}
+
+impl Scope {
+    pub fn variant(&self) -> ScopeVariant {
+        self.variant
+    }
+
+    pub fn is_exclusion(&self) -> bool {
+        self.exclude
+    }
+
+    pub fn matches(&self, s: &str, s_variant: ScopeVariant) -> bool {
+        self.matching_value(s, s_variant).is_some()
+    }
+
+    /// Returns the first value of this scope that matches `s`, if any.
+    pub fn matching_value(&self, s: &str, s_variant: ScopeVariant) -> Option<&ScopeValue> {
+        self.values_for(s_variant)
+            .iter()
+            .find(|value| value.matches(s))
+    }
+
+    /// The values of this scope, if it's of the given variant.
+    pub fn values_for(&self, variant: ScopeVariant) -> &[ScopeValue] {
+        if self.variant == variant {
+            &self.values
+        } else {
+            &[]
+        }
     }
 }
 
 impl FromStr for Scope {
     type Err = anyhow::Error;
 
-    ///grammar -> variant:value,value,*value,...,value
+    ///grammar -> [!]variant:value,value,*value,...,value
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let group = s.split(':').collect::<Vec<_>>();
+        let (exclude, s) = match s.strip_prefix('!') {
+            Some(s) => (true, s),
+            None => (false, s),
+        };
 
-        let [variant, values] = group.as_slice() else {
-            return Err(anyhow!("incorrect number of parts: {:?}", group))
+        let Some((variant, values)) = s.split_once(':') else {
+            return Err(anyhow!("incorrect number of parts: {:?}", s))
                 .with_context(|| {
-                    return format!("\n{}\n\tvariants are one of {}\n\ta value can be a string starting or ending with an '*'", "should be two parts, a variant and values: <variant>:<...values>".yellow(), "att | prop | fn".green())
+                    format!("\n{}\n\tvariants are one of {}\n\ta value can be a string with '*' wildcards, or a /regex/", "should be two parts, a variant and values: <variant>:<...values>".yellow(), "att | prop | fn | el | assign | sel | type | enum | var".green())
                 });
         };
 
-        let values = values
-            .split(',')
+        let variant = match variant {
+            "att" => ScopeVariant::AttrNames,
+            "prop" => ScopeVariant::RecordEntries,
+            "fn" => ScopeVariant::FnCall,
+            "el" => ScopeVariant::Elements,
+            "assign" => ScopeVariant::Assignments,
+            "sel" => ScopeVariant::Selectors,
+            "type" => ScopeVariant::Types,
+            "enum" => ScopeVariant::Enums,
+            "var" => ScopeVariant::Variables,
+            _ => return Err(anyhow!("unrecognized variant: {}", variant)),
+        };
+
+        let values = split_values(values)?
+            .into_iter()
             .filter(|v| !v.is_empty())
             .map(|v| {
                 let mut mt = MatchType::default();
 
-                let [identifier] = v.split('*').filter(|v| !v.is_empty()).collect::<Vec<_>>()[..]
-                else {
-                    return Err(anyhow!("cannot have a wildcard in the middle"));
+                if let Some(regex) = v.strip_prefix('/') {
+                    let end =
+                        regex_end(regex).ok_or_else(|| anyhow!("unterminated regex: {}", v))?;
+                    let (regex, rest) = (&regex[..end], &regex[end + 1..]);
+
+                    let selector = match rest {
+                        "" => None,
+                        rest => Some(Qualifier::Args(rest.parse()?)),
+                    };
+
+                    let pattern = regex::Regex::new(regex)
+                        .with_context(|| format!("invalid regex: {}", v))?;
+
+                    return Ok(ScopeValue(
+                        MatchType::Regex(Pattern(pattern)),
+                        regex.into(),
+                        selector,
+                    ));
+                }
+
+                let (v, selector) = match v.find('[') {
+                    Some(i) => (&v[..i], Some(Qualifier::Args(v[i..].parse()?))),
+                    None => (v, None),
                 };
 
+                let (v, selector) = match v.rsplit_once('.') {
+                    Some(("", _)) => return Err(anyhow!("missing an element name: {}", v)),
+                    Some((element, v)) if variant == ScopeVariant::AttrNames => {
+                        (v, selector.or(Some(Qualifier::Element(element.into()))))
+                    }
+                    _ => (v, selector),
+                };
+
+                let identifier = v.trim_matches('*');
+
+                if identifier.is_empty() {
+                    return Err(anyhow!("a value can't be only wildcards"));
+                }
+
+                if identifier.contains('*') {
+                    return Ok(ScopeValue(
+                        MatchType::Glob(Pattern(glob_regex(v)?)),
+                        v.into(),
+                        selector,
+                    ));
+                }
+
                 if v.starts_with('*') && v.ends_with('*') {
                     mt = MatchType::Contains;
                 } else if v.starts_with('*') {
@@ -82,7 +377,7 @@ impl FromStr for Scope {
                     mt = MatchType::StartsWith;
                 }
 
-                Ok(ScopeValue(mt, identifier.into()))
+                Ok(ScopeValue(mt, identifier.into(), selector))
             })
             .collect::<Result<Vec<_>, anyhow::Error>>()?;
 
@@ -90,35 +385,85 @@ impl FromStr for Scope {
             return Err(anyhow!("at least one value must be provided"));
         }
 
-        let values = values.into();
+        let values: Array<ScopeValue> = values.into();
 
-        let variant = match *variant {
-            "att" => ScopeVariant::AttrNames,
-            "prop" => ScopeVariant::RecordEntries,
-            "fn" => ScopeVariant::FnCall,
-            _ => return Err(anyhow!("unrecognized variant: {}", variant)),
+        if variant != ScopeVariant::FnCall && values.iter().any(|v| v.selector().is_some()) {
+            return Err(anyhow!("only fn scopes can select arguments: {}", s));
+        }
+
+        if exclude && values.iter().any(|v| v.selector().is_some()) {
+            return Err(anyhow!("exclusion scopes can't select arguments: {}", s));
+        }
+
+        Ok(Scope {
+            exclude,
+            variant,
+            values,
+        })
+    }
+}
+
+/// Splits values on ',', except within a /regex/.
+fn split_values(s: &str) -> anyhow::Result<Vec<&str>> {
+    let mut values = vec![];
+    let mut rest = s;
+
+    loop {
+        let end = match rest.strip_prefix('/') {
+            Some(regex) => {
+                let end =
+                    regex_end(regex).ok_or_else(|| anyhow!("unterminated regex: {}", rest))?;
+                rest[end + 2..].find(',').map(|i| i + end + 2)
+            }
+            None => rest.find(','),
         };
 
-        Ok(Scope { variant, values })
+        match end {
+            Some(end) => {
+                values.push(&rest[..end]);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                values.push(rest);
+                return Ok(values);
+            }
+        }
     }
 }
 
+/// The index of the '/' that closes a regex, given what follows the opening one.
+fn regex_end(s: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in s.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '/' => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
 
-    use crate::scope::{MatchType, Scope, ScopeValue, ScopeVariant};
+    use crate::scope::{
+        ArgSelector, MatchType, PathSegment, Qualifier, Scope, ScopeValue, ScopeVariant,
+    };
 
     #[test]
     fn it_parses() {
         assert_eq!(
             Scope::from_str("att:class,className,iconClassName").unwrap(),
             Scope {
+                exclude: false,
                 variant: ScopeVariant::AttrNames,
                 values: vec![
-                    ScopeValue(MatchType::Is, "class".into()),
-                    ScopeValue(MatchType::Is, "className".into()),
-                    ScopeValue(MatchType::Is, "iconClassName".into())
+                    ScopeValue(MatchType::Is, "class".into(), None),
+                    ScopeValue(MatchType::Is, "className".into(), None),
+                    ScopeValue(MatchType::Is, "iconClassName".into(), None)
                 ]
                 .into_boxed_slice()
             }
@@ -127,10 +472,11 @@ mod tests {
         assert_eq!(
             Scope::from_str("prop:classes,className,").unwrap(),
             Scope {
+                exclude: false,
                 variant: ScopeVariant::RecordEntries,
                 values: vec![
-                    ScopeValue(MatchType::Is, "classes".into(),),
-                    ScopeValue(MatchType::Is, "className".into())
+                    ScopeValue(MatchType::Is, "classes".into(), None),
+                    ScopeValue(MatchType::Is, "className".into(), None)
                 ]
                 .into_boxed_slice()
             }
@@ -139,8 +485,9 @@ mod tests {
         assert_eq!(
             Scope::from_str("fn:cva").unwrap(),
             Scope {
+                exclude: false,
                 variant: ScopeVariant::FnCall,
-                values: vec![ScopeValue(MatchType::Is, "cva".into())].into_boxed_slice()
+                values: vec![ScopeValue(MatchType::Is, "cva".into(), None)].into_boxed_slice()
             }
         )
     }
@@ -150,10 +497,11 @@ mod tests {
         assert_eq!(
             Scope::from_str("att:class,*ClassName").unwrap(),
             Scope {
+                exclude: false,
                 variant: ScopeVariant::AttrNames,
                 values: vec![
-                    ScopeValue(MatchType::Is, "class".into()),
-                    ScopeValue(MatchType::EndWith, "ClassName".into()),
+                    ScopeValue(MatchType::Is, "class".into(), None),
+                    ScopeValue(MatchType::EndWith, "ClassName".into(), None),
                 ]
                 .into_boxed_slice()
             }
@@ -162,10 +510,11 @@ mod tests {
         assert_eq!(
             Scope::from_str("prop:classes,***ClassName,").unwrap(),
             Scope {
+                exclude: false,
                 variant: ScopeVariant::RecordEntries,
                 values: vec![
-                    ScopeValue(MatchType::Is, "classes".into(),),
-                    ScopeValue(MatchType::EndWith, "ClassName".into())
+                    ScopeValue(MatchType::Is, "classes".into(), None),
+                    ScopeValue(MatchType::EndWith, "ClassName".into(), None)
                 ]
                 .into_boxed_slice()
             }
@@ -177,10 +526,11 @@ mod tests {
         assert_eq!(
             Scope::from_str("att:class,class**").unwrap(),
             Scope {
+                exclude: false,
                 variant: ScopeVariant::AttrNames,
                 values: vec![
-                    ScopeValue(MatchType::Is, "class".into()),
-                    ScopeValue(MatchType::StartsWith, "class".into()),
+                    ScopeValue(MatchType::Is, "class".into(), None),
+                    ScopeValue(MatchType::StartsWith, "class".into(), None),
                 ]
                 .into_boxed_slice()
             }
@@ -189,16 +539,51 @@ mod tests {
         assert_eq!(
             Scope::from_str("prop:class**").unwrap(),
             Scope {
+                exclude: false,
                 variant: ScopeVariant::RecordEntries,
-                values: vec![ScopeValue(MatchType::StartsWith, "class".into())].into_boxed_slice()
+                values: vec![ScopeValue(MatchType::StartsWith, "class".into(), None)]
+                    .into_boxed_slice()
             }
         );
     }
 
     #[test]
-    fn it_rejects_middle_wildcard() {
-        Scope::from_str("att:class,class*name").unwrap_err();
-        Scope::from_str("prop:class*name").unwrap_err();
+    fn it_parses_middle_wildcard_as_glob() {
+        let scope = Scope::from_str("att:class,data-*-class").unwrap();
+        assert!(matches!(scope.values[1].0, MatchType::Glob(_)));
+        assert_eq!(scope.values[1].to_string(), "data-*-class");
+
+        assert!(scope.matches("data-icon-class", ScopeVariant::AttrNames));
+        assert!(scope.matches("data--class", ScopeVariant::AttrNames));
+        assert!(!scope.matches("data-class", ScopeVariant::AttrNames));
+        assert!(!scope.matches("data-icon-className", ScopeVariant::AttrNames));
+
+        let scope = Scope::from_str("fn:use*Styles*").unwrap();
+        assert!(scope.matches("useButtonStyles", ScopeVariant::FnCall));
+        assert!(scope.matches("useStylesheet", ScopeVariant::FnCall));
+        assert!(!scope.matches("makeStyles", ScopeVariant::FnCall));
+
+        Scope::from_str("att:**").unwrap_err();
+    }
+
+    #[test]
+    fn it_parses_regex() {
+        let scope = Scope::from_str("att:/^(icon|label)ClassNames?$/,/a{1,2}:b/,class").unwrap();
+        assert_eq!(scope.values.len(), 3);
+        assert_eq!(scope.values[0].to_string(), "/^(icon|label)ClassNames?$/");
+        assert_eq!(scope.values[1].to_string(), "/a{1,2}:b/");
+
+        assert!(scope.matches("iconClassName", ScopeVariant::AttrNames));
+        assert!(scope.matches("labelClassNames", ScopeVariant::AttrNames));
+        assert!(scope.matches("class", ScopeVariant::AttrNames));
+        assert!(!scope.matches("bodyClassName", ScopeVariant::AttrNames));
+
+        let scope = Scope::from_str(r"fn:/^use\/?\w+Styles$/[0]").unwrap();
+        assert!(scope.values[0].selector().is_some());
+        assert!(scope.matches("useCardStyles", ScopeVariant::FnCall));
+
+        Scope::from_str("att:/^unterminated").unwrap_err();
+        Scope::from_str("att:/(/").unwrap_err();
     }
 
     #[test]
@@ -210,10 +595,11 @@ mod tests {
     #[test]
     fn it_matches() {
         let scope = Scope {
+            exclude: false,
             variant: ScopeVariant::RecordEntries,
             values: vec![
-                ScopeValue(MatchType::Is, "classes".into()),
-                ScopeValue(MatchType::Is, "className".into()),
+                ScopeValue(MatchType::Is, "classes".into(), None),
+                ScopeValue(MatchType::Is, "className".into(), None),
             ]
             .into_boxed_slice(),
         };
@@ -227,8 +613,10 @@ mod tests {
     #[test]
     fn it_matches_ends() {
         let scope = Scope {
+            exclude: false,
             variant: ScopeVariant::AttrNames,
-            values: vec![ScopeValue(MatchType::EndWith, "ClassName".into())].into_boxed_slice(),
+            values: vec![ScopeValue(MatchType::EndWith, "ClassName".into(), None)]
+                .into_boxed_slice(),
         };
 
         assert!(!scope.matches("className", ScopeVariant::AttrNames));
@@ -242,8 +630,10 @@ mod tests {
     #[test]
     fn it_matches_starts() {
         let scope = Scope {
+            exclude: false,
             variant: ScopeVariant::AttrNames,
-            values: vec![ScopeValue(MatchType::StartsWith, "class".into())].into_boxed_slice(),
+            values: vec![ScopeValue(MatchType::StartsWith, "class".into(), None)]
+                .into_boxed_slice(),
         };
 
         assert!(!scope.matches("class", ScopeVariant::RecordEntries));
@@ -254,8 +644,9 @@ mod tests {
     #[test]
     fn it_matches_contains() {
         let scope = Scope {
+            exclude: false,
             variant: ScopeVariant::AttrNames,
-            values: vec![ScopeValue(MatchType::Contains, "class".into())].into_boxed_slice(),
+            values: vec![ScopeValue(MatchType::Contains, "class".into(), None)].into_boxed_slice(),
         };
 
         assert!(!scope.matches("class", ScopeVariant::RecordEntries));
@@ -265,12 +656,143 @@ mod tests {
         assert!(scope.matches("buttonclassName", ScopeVariant::AttrNames));
 
         let scope = Scope {
+            exclude: false,
             variant: ScopeVariant::AttrNames,
-            values: vec![ScopeValue(MatchType::Contains, "Class".into())].into_boxed_slice(),
+            values: vec![ScopeValue(MatchType::Contains, "Class".into(), None)].into_boxed_slice(),
         };
 
         assert!(scope.matches("iconClassName", ScopeVariant::AttrNames));
         assert!(scope.matches("bodyClassName", ScopeVariant::AttrNames));
         assert!(scope.matches("buttonClassName", ScopeVariant::AttrNames));
     }
+
+    #[test]
+    fn it_parses_arg_selectors() {
+        assert_eq!(
+            Scope::from_str("fn:cva[0],cva[1].variants.*.*,tv[0].slots[*]").unwrap(),
+            Scope {
+                exclude: false,
+                variant: ScopeVariant::FnCall,
+                values: vec![
+                    ScopeValue(
+                        MatchType::Is,
+                        "cva".into(),
+                        Some(Qualifier::Args(ArgSelector {
+                            index: 0,
+                            path: vec![].into_boxed_slice()
+                        }))
+                    ),
+                    ScopeValue(
+                        MatchType::Is,
+                        "cva".into(),
+                        Some(Qualifier::Args(ArgSelector {
+                            index: 1,
+                            path: vec![
+                                PathSegment::Key("variants".into()),
+                                PathSegment::Any,
+                                PathSegment::Any
+                            ]
+                            .into_boxed_slice()
+                        }))
+                    ),
+                    ScopeValue(
+                        MatchType::Is,
+                        "tv".into(),
+                        Some(Qualifier::Args(ArgSelector {
+                            index: 0,
+                            path: vec![PathSegment::Key("slots".into()), PathSegment::Any]
+                                .into_boxed_slice()
+                        }))
+                    ),
+                ]
+                .into_boxed_slice()
+            }
+        );
+
+        assert_eq!(
+            Scope::from_str("fn:cva[1].variants.*.*").unwrap().values[0].to_string(),
+            "cva[1].variants.*.*"
+        );
+    }
+
+    #[test]
+    fn it_rejects_bad_arg_selectors() {
+        Scope::from_str("fn:cva[").unwrap_err();
+        Scope::from_str("fn:cva[x]").unwrap_err();
+        Scope::from_str("fn:cva[0]..variants").unwrap_err();
+        Scope::from_str("fn:cva[*]").unwrap_err();
+        Scope::from_str("prop:classes[0]").unwrap_err();
+    }
+
+    #[test]
+    fn it_parses_exclusions() {
+        assert_eq!(
+            Scope::from_str("!fn:t,i18n*").unwrap(),
+            Scope {
+                exclude: true,
+                variant: ScopeVariant::FnCall,
+                values: vec![
+                    ScopeValue(MatchType::Is, "t".into(), None),
+                    ScopeValue(MatchType::StartsWith, "i18n".into(), None),
+                ]
+                .into_boxed_slice()
+            }
+        );
+
+        assert!(!Scope::from_str("prop:testId").unwrap().is_exclusion());
+        assert!(Scope::from_str("!prop:testId").unwrap().is_exclusion());
+        Scope::from_str("!fn:cva[0]").unwrap_err();
+        Scope::from_str("!!fn:t").unwrap_err();
+    }
+
+    #[test]
+    fn it_parses_element_qualifiers() {
+        assert_eq!(
+            Scope::from_str("att:Icon.color,Dialog.Panel.*ClassName,className").unwrap(),
+            Scope {
+                exclude: false,
+                variant: ScopeVariant::AttrNames,
+                values: vec![
+                    ScopeValue(
+                        MatchType::Is,
+                        "color".into(),
+                        Some(Qualifier::Element("Icon".into()))
+                    ),
+                    ScopeValue(
+                        MatchType::EndWith,
+                        "ClassName".into(),
+                        Some(Qualifier::Element("Dialog.Panel".into()))
+                    ),
+                    ScopeValue(MatchType::Is, "className".into(), None),
+                ]
+                .into_boxed_slice()
+            }
+        );
+
+        let scope = Scope::from_str("att:Icon.color").unwrap();
+        assert_eq!(scope.values[0].to_string(), "Icon.color");
+        assert!(scope.values[0].matches_element(Some("Icon")));
+        assert!(!scope.values[0].matches_element(Some("Button")));
+        assert!(!scope.values[0].matches_element(None));
+
+        assert_eq!(
+            Scope::from_str("el:Dialog.Panel").unwrap().values[0],
+            ScopeValue(MatchType::Is, "Dialog.Panel".into(), None)
+        );
+
+        Scope::from_str("att:.color").unwrap_err();
+        Scope::from_str("att:Icon.color[0]").unwrap_err();
+    }
+
+    #[test]
+    fn it_displays_scopes_as_parsed() {
+        for scope in [
+            "att:className,*ClassName",
+            "!prop:testId",
+            "fn:cva[0],cva[1].variants.*.*",
+            "att:Icon.color,Dialog.Panel.*ClassName",
+        ] {
+            assert_eq!(Scope::from_str(scope).unwrap().to_string(), scope);
+        }
+    }
 }
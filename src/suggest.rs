@@ -0,0 +1,299 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use cnat::scope::{Scope, ScopeVariant};
+use swc_common::{BytePos, SourceMap};
+use swc_ecma_ast::{AssignTarget, Callee, JSXAttrName, Pat, SimpleAssignTarget};
+use swc_ecma_visit::{Visit, VisitWith};
+
+use crate::report::Report;
+use crate::transform::{jsx_element_name, member_chain, parse, prop_key};
+
+/// How many locations to show for every suggested scope.
+const EXAMPLES: usize = 3;
+
+/// Finds the strings made mostly of classes that aren't in scope yet, and the scopes that
+/// would take them in.
+pub struct Suggest<'cn> {
+    class_names: &'cn [cnat::Str],
+    /// the scopes already in use, printed ahead of the suggested ones
+    scopes: &'cn [Scope],
+    /// (file, line, column) of every string already in scope
+    covered: HashSet<(PathBuf, usize, usize)>,
+    /// scope -> every string it would take in
+    hits: BTreeMap<(ScopeVariant, String), Vec<Hit>>,
+}
+
+/// A string's location, and the element it's an attribute of.
+type Hit = (Location, Option<String>);
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Location {
+    file: PathBuf,
+    line: usize,
+    column: usize,
+}
+
+/// A suggested scope, with every string it would take in.
+struct Suggestion<'a> {
+    scope: String,
+    locations: Vec<&'a Location>,
+}
+
+impl<'cn> Suggest<'cn> {
+    /// `report` is a dry run's, with `scopes`.
+    pub fn new(class_names: &'cn [cnat::Str], scopes: &'cn [Scope], report: &Report) -> Self {
+        Self {
+            class_names,
+            scopes,
+            covered: report
+                .replacements()
+                .iter()
+                .map(|r| (r.file.clone(), r.line, r.column))
+                .collect(),
+            hits: BTreeMap::new(),
+        }
+    }
+
+    pub fn scan_file(&mut self, source_file: &Path) -> anyhow::Result<()> {
+        let (cm, _, program) = parse(source_file, None)?;
+
+        let mut strings = ClassStrings {
+            class_names: self.class_names,
+            contexts: vec![],
+            element: None,
+            found: vec![],
+        };
+        program.visit_with(&mut strings);
+
+        for (pos, (variant, name), element) in strings.found {
+            let location = Location::new(source_file, &cm, pos);
+            let key = (location.file.clone(), location.line, location.column);
+            if self.covered.contains(&key) {
+                continue;
+            }
+
+            self.hits
+                .entry((variant, name))
+                .or_default()
+                .push((location, element));
+        }
+
+        Ok(())
+    }
+
+    /// Most hits first.
+    fn suggestions(&self) -> Vec<Suggestion<'_>> {
+        let mut suggestions: Vec<_> = self
+            .hits
+            .iter()
+            .map(|((variant, name), hits)| {
+                let elements: BTreeSet<_> = hits.iter().map(|(_, e)| e.as_deref()).collect();
+
+                // an attribute only ever found on one element is qualified with it,
+                // e.g. `att:Icon.color`
+                let scope = match (variant, elements.first()) {
+                    (ScopeVariant::AttrNames, Some(Some(element))) if elements.len() == 1 => {
+                        format!("{variant}:{element}.{name}")
+                    }
+                    _ => format!("{variant}:{name}"),
+                };
+
+                let mut locations: Vec<_> = hits.iter().map(|(location, _)| location).collect();
+                locations.sort();
+
+                Suggestion { scope, locations }
+            })
+            .collect();
+
+        suggestions.sort_by_key(|s| std::cmp::Reverse(s.locations.len()));
+        suggestions
+    }
+
+    pub fn write(&self, mut out: impl Write) -> anyhow::Result<()> {
+        let suggestions = self.suggestions();
+
+        if suggestions.is_empty() {
+            writeln!(out, "no strings of classes found out of scope")?;
+            return Ok(());
+        }
+
+        for suggestion in &suggestions {
+            writeln!(
+                out,
+                "{:>5}  {}",
+                suggestion.locations.len(),
+                suggestion.scope
+            )?;
+            for location in suggestion.locations.iter().take(EXAMPLES) {
+                writeln!(
+                    out,
+                    "       {}:{}:{}",
+                    location.file.display(),
+                    location.line,
+                    location.column
+                )?;
+            }
+        }
+
+        // the flag replaces the scopes in use, so they come along
+        let scopes: Vec<_> = self
+            .scopes
+            .iter()
+            .map(|s| s.to_string())
+            .chain(suggestions.iter().map(|s| s.scope.clone()))
+            .collect();
+        writeln!(out)?;
+        writeln!(out, "--scopes '{}'", scopes.join(" "))?;
+
+        Ok(())
+    }
+}
+
+impl Location {
+    fn new(file: &Path, cm: &SourceMap, pos: BytePos) -> Self {
+        let loc = cm.lookup_char_pos(pos);
+        Self {
+            file: file.to_path_buf(),
+            line: loc.line,
+            column: loc.col.0 + 1,
+        }
+    }
+}
+
+/// Collects the strings made mostly of classes, with the innermost syntactic context that a
+/// scope could match.
+struct ClassStrings<'cn> {
+    class_names: &'cn [cnat::Str],
+    contexts: Vec<(ScopeVariant, String)>,
+    /// The name of the jsx element whose attributes are being visited.
+    element: Option<String>,
+    found: Vec<(BytePos, (ScopeVariant, String), Option<String>)>,
+}
+
+impl<'cn> ClassStrings<'cn> {
+    fn visit_in_context<N: VisitWith<Self>>(&mut self, variant: ScopeVariant, name: &str, n: &N) {
+        self.contexts.push((variant, name.to_string()));
+        n.visit_with(self);
+        self.contexts.pop();
+    }
+
+    /// Whether more than half of the tokens are classes from the css input.
+    fn is_mostly_classes(&self, s: &str) -> bool {
+        let tokens: Vec<_> = s.split_ascii_whitespace().collect();
+        let classes = tokens
+            .iter()
+            .filter(|token| {
+                let class = token.rsplit(':').next().unwrap_or(token);
+                self.class_names.iter().any(|name| name == class)
+            })
+            .count();
+
+        classes * 2 > tokens.len()
+    }
+
+    /// The context to suggest a scope for; object keys only when nothing else encloses the
+    /// string, so `cva("...", { variants: { size: { sm: "h-8" } } })` suggests `fn:cva`.
+    fn context(&self) -> Option<&(ScopeVariant, String)> {
+        self.contexts
+            .iter()
+            .rev()
+            .find(|(variant, _)| *variant != ScopeVariant::RecordEntries)
+            .or_else(|| self.contexts.last())
+    }
+}
+
+impl<'cn> Visit for ClassStrings<'cn> {
+    fn visit_import_decl(&mut self, _: &swc_ecma_ast::ImportDecl) {}
+
+    fn visit_jsx_opening_element(&mut self, n: &swc_ecma_ast::JSXOpeningElement) {
+        let element = self.element.replace(jsx_element_name(&n.name));
+        n.visit_children_with(self);
+        self.element = element;
+    }
+
+    fn visit_jsx_attr(&mut self, n: &swc_ecma_ast::JSXAttr) {
+        match &n.name {
+            JSXAttrName::Ident(name) => {
+                self.visit_in_context(ScopeVariant::AttrNames, &name.sym, &n.value)
+            }
+            _ => n.visit_children_with(self),
+        }
+    }
+
+    fn visit_call_expr(&mut self, n: &swc_ecma_ast::CallExpr) {
+        let chain = match &n.callee {
+            Callee::Expr(expr) => member_chain(expr),
+            _ => vec![],
+        };
+
+        n.callee.visit_with(self);
+        match chain.len() {
+            0 => n.args.visit_with(self),
+            // `classList.add` rather than `document.body.classList.add`
+            len => {
+                let name = chain[len.saturating_sub(2)..].join(".");
+                self.visit_in_context(ScopeVariant::FnCall, &name, &n.args)
+            }
+        }
+    }
+
+    fn visit_assign_expr(&mut self, n: &swc_ecma_ast::AssignExpr) {
+        let AssignTarget::Simple(SimpleAssignTarget::Member(member)) = &n.left else {
+            n.visit_children_with(self);
+            return;
+        };
+
+        match &member.prop {
+            swc_ecma_ast::MemberProp::Ident(prop) => {
+                self.visit_in_context(ScopeVariant::Assignments, &prop.sym, &n.right)
+            }
+            _ => n.right.visit_with(self),
+        }
+    }
+
+    fn visit_key_value_prop(&mut self, n: &swc_ecma_ast::KeyValueProp) {
+        match prop_key(&n.key) {
+            Some(key) => {
+                let key = key.to_string();
+                self.visit_in_context(ScopeVariant::RecordEntries, &key, &n.value)
+            }
+            None => n.value.visit_with(self),
+        }
+    }
+
+    fn visit_ts_type_alias_decl(&mut self, n: &swc_ecma_ast::TsTypeAliasDecl) {
+        self.visit_in_context(ScopeVariant::Types, &n.id.sym, &n.type_ann)
+    }
+
+    fn visit_ts_enum_decl(&mut self, n: &swc_ecma_ast::TsEnumDecl) {
+        self.visit_in_context(ScopeVariant::Enums, &n.id.sym, &n.members)
+    }
+
+    fn visit_var_declarator(&mut self, n: &swc_ecma_ast::VarDeclarator) {
+        match &n.name {
+            Pat::Ident(binding) => {
+                self.visit_in_context(ScopeVariant::Variables, &binding.id.sym, &n.init)
+            }
+            _ => n.init.visit_with(self),
+        }
+    }
+
+    fn visit_str(&mut self, n: &swc_ecma_ast::Str) {
+        if !self.is_mostly_classes(&n.value) {
+            return;
+        }
+
+        let Some(context) = self.context().cloned() else {
+            return;
+        };
+
+        let element = match context.0 {
+            ScopeVariant::AttrNames => self.element.clone(),
+            _ => None,
+        };
+
+        self.found.push((n.span.lo, context, element));
+    }
+}
@@ -1,25 +1,79 @@
 use anyhow::{anyhow, Context};
 use colored::Colorize;
+use std::collections::{HashMap, HashSet};
 use std::ffi::OsStr;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::vec;
+use swc_common::comments::{Comments, SingleThreadedComments};
 use swc_common::sync::Lrc;
+use swc_common::Spanned;
 use swc_common::{
     errors::{ColorConfig, Handler},
-    SourceMap,
+    BytePos, FileName, SourceFile, SourceMap, Span,
+};
+use swc_ecma_ast::{
+    AssignTarget, BinaryOp, Callee, Decl, EsVersion, Expr, ExprOrSpread, ImportSpecifier,
+    JSXAttrName, JSXAttrOrSpread, JSXAttrValue, JSXElementName, JSXExpr, JSXObject, Lit,
+    MemberExpr, MemberProp, ModuleExportName, OptChainBase, Pat, Program, Prop, PropName,
+    PropOrSpread, SimpleAssignTarget, TsEntityName, TsType, TsTypeRef,
 };
-use swc_ecma_ast::{Callee, EsVersion, Expr, Ident, JSXAttrName, PropName};
 use swc_ecma_parser::{parse_file_as_program, Syntax};
 use swc_ecma_visit::{VisitMut, VisitMutWith};
 
-use cnat::scope::{Scope, ScopeVariant};
+use cnat::scope::{PathSegment, Scope, ScopeValue, ScopeVariant};
+
+use crate::bindings::{Bindings, FollowBindings};
+use crate::directives::Directives;
+use crate::explain::{Explain, LiteralKind, TokenMatch};
+use crate::generated::SkipGenerated;
+use crate::journal::{write_atomically, Journal};
+use crate::lint::UnknownClasses;
+use crate::literal::{tokens, RawStr};
+use crate::modules::ModuleResolver;
+use crate::report::{FindingKind, PendingFinding, PendingReplacement, Report, ScopeEntry};
 
 pub struct ApplyTailwindPrefix<'s, 'cn, 'scopes> {
     pub prefix: &'s str,
     class_names: &'cn [cnat::Str],
     scopes: &'scopes [Scope],
     is_in_scope: bool,
+    active_scope: Option<ScopeEntry>,
     replacements: Vec<replacements::Replacement>,
+    pending: Vec<PendingReplacement>,
+    pending_findings: Vec<PendingFinding>,
+    pub report: Report,
+    /// Don't write any files, only record what would be replaced.
+    pub dry_run: bool,
+    /// Report every class that would be prefixed as a finding.
+    pub check: bool,
+    /// Report classes, in scope, that aren't in the css input.
+    pub unknown_classes: Option<UnknownClasses>,
+    /// Record why every string literal was or wasn't transformed.
+    pub explain: Option<Explain>,
+    /// Skip the files that look generated or minified.
+    pub skip_generated: Option<SkipGenerated>,
+    /// Back up every file before it's written to, see [`Journal`].
+    pub journal: Option<Journal>,
+    /// Whether strings in scope are css selectors, see [`ScopeVariant::Selectors`].
+    is_in_selector: bool,
+    /// The name of the jsx element whose attributes are being visited.
+    element: Option<String>,
+    /// The comment directives of the file being visited.
+    directives: Directives,
+    bindings: Bindings,
+    /// file -> exported name -> the scope other files use it in
+    exports_to_follow: HashMap<PathBuf, HashMap<String, ScopeEntry>>,
+}
+
+/// A piece of a string built at runtime.
+enum Segment<'a> {
+    /// `raw` maps the offsets into `text` to the source, for a string whose escapes make them differ.
+    Text {
+        lo: BytePos,
+        text: &'a str,
+        raw: Option<RawStr<'a>>,
+    },
+    Hole(Span),
 }
 
 impl<'s, 'cn, 'scopes> ApplyTailwindPrefix<'s, 'cn, 'scopes> {
@@ -29,122 +83,739 @@ impl<'s, 'cn, 'scopes> ApplyTailwindPrefix<'s, 'cn, 'scopes> {
             class_names,
             scopes,
             is_in_scope: false,
+            active_scope: None,
             replacements: vec![],
+            pending: vec![],
+            pending_findings: vec![],
+            report: Report::default(),
+            dry_run: false,
+            check: false,
+            unknown_classes: None,
+            explain: None,
+            skip_generated: None,
+            journal: None,
+            is_in_selector: false,
+            element: None,
+            directives: Directives::default(),
+            bindings: Bindings::default(),
+            exports_to_follow: HashMap::new(),
         }
     }
 
     /// Returns the number of files transformed.
-    pub fn prefix_all_classes_in_dir(&mut self, path: &Path) -> anyhow::Result<usize> {
-        assert!(path.is_dir());
+    /// Imports are followed across the directories, e.g. an app importing the styles of a package.
+    pub fn prefix_all_classes_in_dirs(&mut self, paths: &[PathBuf]) -> anyhow::Result<usize> {
+        let contexts: Vec<_> = paths.iter().map(|path| self.context(path)).collect();
+        self.exports_to_follow = self.find_imports_used_in_scope(&contexts);
 
         let mut edit_count = 0;
-
-        for r in ignore::Walk::new(path) {
-            match r {
-                Ok(entry) => {
-                    let filepath = entry.path();
-                    let is_supported_file = filepath.is_file()
-                        && filepath
-                            .extension()
-                            .map(|e| ["ts", "js", "jsx", "tsx"].map(OsStr::new).contains(&e))
-                            .unwrap_or(false);
-
-                    if !is_supported_file {
-                        continue;
-                    }
-
-                    match self.prefix_classes_in_file(filepath) {
-                        Ok(Some(())) => {
-                            edit_count += 1;
-                        }
-                        Err(err) => {
-                            eprintln!(
-                                "{} failed to process file, {}: {err:#}",
-                                "[ERROR]".red(),
-                                filepath.display()
-                            )
-                        }
-                        Ok(None) => {}
-                    }
+        for filepath in contexts.iter().flat_map(|(files, _)| files) {
+            match self.prefix_classes_in_file(filepath) {
+                Ok(Some(())) => {
+                    edit_count += 1;
                 }
-                Err(err) => eprintln!("[Error] {err:#}"),
-            };
+                Err(err) => {
+                    eprintln!(
+                        "{} failed to process file, {}: {err:#}",
+                        "[ERROR]".red(),
+                        filepath.display()
+                    )
+                }
+                Ok(None) => {}
+            }
         }
 
         Ok(edit_count)
     }
 
-    pub fn prefix_classes_in_file(&mut self, source_file: &Path) -> anyhow::Result<Option<()>> {
-        let cm: Lrc<SourceMap> = Default::default();
-        let error_handler =
-            Handler::with_tty_emitter(ColorConfig::Auto, true, false, Some(cm.clone()));
-
-        let fm = cm
-            .load_file(source_file)
-            .context("failed to load source file")?;
-
-        let syntax = match source_file.extension().and_then(|e| e.to_str()) {
-            Some("js") | Some("jsx") => Syntax::Es(swc_ecma_parser::EsConfig {
-                jsx: true,
-                ..Default::default()
-            }),
-            Some("ts") => Syntax::Typescript(Default::default()),
-            Some("tsx") => Syntax::Typescript(swc_ecma_parser::TsConfig {
-                tsx: true,
-                ..Default::default()
-            }),
-            None => {
-                return Err(anyhow!(
-                    "unknown filetype, missing extension: {}",
-                    source_file.display()
-                ))
+    /// Builds the module graph over the given directories, so that a single file is transformed
+    /// with the bindings it exports to them, as it is by [`Self::prefix_all_classes_in_dirs`].
+    pub fn follow_imports_in_dirs(&mut self, paths: &[PathBuf]) {
+        let contexts: Vec<_> = paths.iter().map(|path| self.context(path)).collect();
+        self.exports_to_follow = self.find_imports_used_in_scope(&contexts);
+    }
+
+    /// The files of a directory to transform, and the resolver of their imports.
+    fn context(&self, path: &Path) -> (Vec<PathBuf>, ModuleResolver) {
+        assert!(path.is_dir());
+
+        let mut files = collect_files(path);
+        if let Some(skip) = &self.skip_generated {
+            skip.retain(&mut files);
+        }
+
+        let resolver = ModuleResolver::new(path).unwrap_or_else(|err| {
+            eprintln!(
+                "{} {err:#}, only relative imports will be followed",
+                "[WARN]".yellow()
+            );
+            ModuleResolver::default()
+        });
+
+        (files, resolver)
+    }
+
+    /// Builds the module graph over the files of every context, to find the exported bindings
+    /// that other files import and use only in scope, in every one of them.
+    /// Returns file -> exported name -> the scope it's used in.
+    fn find_imports_used_in_scope(
+        &mut self,
+        contexts: &[(Vec<PathBuf>, ModuleResolver)],
+    ) -> HashMap<PathBuf, HashMap<String, ScopeEntry>> {
+        let mut exports: HashMap<PathBuf, HashMap<String, ScopeEntry>> = HashMap::new();
+        let mut used_out_of_scope: HashSet<(PathBuf, String)> = HashSet::new();
+        // only the files transformed afterwards are explained
+        let explain = self.explain.take();
+
+        let files = contexts
+            .iter()
+            .flat_map(|(files, resolver)| files.iter().map(move |file| (file, resolver)));
+        for (file, resolver) in files {
+            let comments = SingleThreadedComments::default();
+            let Ok((_, fm, mut program)) = parse(file, Some(&comments)) else {
+                continue;
+            };
+            self.directives = Directives::new(&fm, &comments);
+
+            program.visit_mut_children_with(self);
+
+            for (src, imported, scope) in self.bindings.imports_to_follow() {
+                if let Some(module) = resolver.resolve(file, src) {
+                    exports
+                        .entry(module)
+                        .or_default()
+                        .entry(imported.to_string())
+                        .or_insert_with(|| scope.clone());
+                }
+            }
+            for (src, imported) in self.bindings.imports_out_of_scope() {
+                if let Some(module) = resolver.resolve(file, src) {
+                    used_out_of_scope.insert((module, imported.to_string()));
+                }
             }
-            ext => return Err(anyhow!("unknown filetype: {ext:?}")),
-        };
 
-        let mut errors = vec![];
-        let mut program = parse_file_as_program(&fm, syntax, EsVersion::Es2015, None, &mut errors)
-            .map_err(|e| e.into_diagnostic(&error_handler).emit())
-            .expect("failed to parse source code file");
+            self.bindings.clear();
+            self.replacements.clear();
+            self.pending.clear();
+            self.pending_findings.clear();
+        }
+
+        for (module, imported) in used_out_of_scope {
+            if let Some(names) = exports.get_mut(&module) {
+                names.remove(&imported);
+            }
+        }
+        self.explain = explain;
+
+        exports
+    }
+
+    pub fn prefix_classes_in_file(&mut self, source_file: &Path) -> anyhow::Result<Option<()>> {
+        let comments = SingleThreadedComments::default();
+        let (cm, fm, mut program) = parse(source_file, Some(&comments))?;
+        self.directives = Directives::new(&fm, &comments);
 
         program.visit_mut_children_with(self);
 
+        let exported = source_file
+            .canonicalize()
+            .ok()
+            .and_then(|f| self.exports_to_follow.get(&f));
+        let bindings = self.bindings.to_follow(exported);
+        if !bindings.is_empty() {
+            program.visit_mut_with(&mut FollowBindings {
+                bindings,
+                apply: self,
+            });
+        }
+        // after following, which records the references in the initializers it visits
+        self.bindings.clear();
+
+        self.replacements.sort_by_key(|r| r.start());
+        self.replacements.dedup_by_key(|r| r.start());
+
+        self.report
+            .record_findings(source_file, &cm, std::mem::take(&mut self.pending_findings));
+
+        if let Some(explain) = &mut self.explain {
+            explain.record(&cm);
+        }
+
         if self.replacements.is_empty() {
             return Ok(None);
         }
 
-        let contents = std::fs::read(source_file).context("failed to file for writing")?;
+        if self.dry_run {
+            self.report
+                .record(source_file, &cm, std::mem::take(&mut self.pending));
+            self.replacements.clear();
+
+            if self.check {
+                eprintln!(
+                    "[INFO] would transform {}",
+                    source_file.display().to_string().yellow()
+                );
+            }
+
+            return Ok(Some(()));
+        }
+
+        // taken now, so a file skipped below doesn't leave them to the next one
+        let mut replacements = std::mem::take(&mut self.replacements);
+        let pending = std::mem::take(&mut self.pending);
 
         eprintln!("[INFO] reading to transform {}", source_file.display());
 
-        let contents = replacements::Replacement::apply_all(&mut self.replacements, contents);
-        std::fs::write(source_file, contents)?;
+        let original = std::fs::read(source_file).context("failed to read file for writing")?;
+        let contents = replacements::Replacement::apply_to_parsed(
+            &mut replacements,
+            fm.src.as_bytes(),
+            &original,
+        )?;
+
+        if let Some(journal) = &mut self.journal {
+            journal.record(source_file, &original, &contents)?;
+        }
+        write_atomically(source_file, &contents)?;
+
+        self.report.record(source_file, &cm, pending);
 
         eprintln!(
             "[INFO] transformed {}",
             source_file.display().to_string().green()
         );
 
-        self.replacements.clear();
-
         Ok(Some(()))
     }
 
-    fn starts_a_valid_scope(&self, ident: &Ident, variant: ScopeVariant) -> bool {
-        let ident = ident.sym.as_str();
+    fn starts_a_valid_scope(&self, ident: &str, variant: ScopeVariant) -> Option<ScopeEntry> {
         self.scopes
             .iter()
-            .any(|scope| scope.matches(ident, variant))
+            .filter(|scope| !scope.is_exclusion())
+            .find_map(|scope| self.matching_value(scope, ident, variant))
+            .map(|value| ScopeEntry::new(variant, value))
+    }
+
+    fn is_excluded(&self, ident: &str, variant: ScopeVariant) -> bool {
+        self.scopes
+            .iter()
+            .filter(|scope| scope.is_exclusion())
+            .any(|scope| self.matching_value(scope, ident, variant).is_some())
+    }
+
+    /// Like [`Scope::matching_value`], but also checks the element an attribute is on.
+    fn matching_value(
+        &self,
+        scope: &'scopes Scope,
+        ident: &str,
+        variant: ScopeVariant,
+    ) -> Option<&'scopes ScopeValue> {
+        scope
+            .values_for(variant)
+            .iter()
+            .find(|value| value.matches(ident) && value.matches_element(self.element.as_deref()))
+    }
+
+    /// Warns about the class tokens, made of the given segments, that are only partially
+    /// literal text, like `text-${color}-600` or `"bg-" + variant`.
+    fn report_dynamic_classes(&mut self, segments: &[Segment]) {
+        struct Token {
+            span: Span,
+            pattern: String,
+            fragments: Vec<String>,
+            has_hole: bool,
+        }
+
+        let mut tokens = vec![];
+        let mut current: Option<Token> = None;
+
+        for segment in segments {
+            match segment {
+                Segment::Text { lo, text, raw } => {
+                    let raw_offset = |i: usize| raw.as_ref().map_or(i, |raw| raw.raw_offset(i));
+
+                    for (i, c) in text.char_indices() {
+                        if c.is_ascii_whitespace() {
+                            tokens.extend(current.take());
+                            continue;
+                        }
+
+                        let pos = *lo + BytePos(raw_offset(i) as u32);
+                        let token = current.get_or_insert_with(|| Token {
+                            span: Span::new(pos, pos, Default::default()),
+                            pattern: String::new(),
+                            fragments: vec![],
+                            has_hole: false,
+                        });
+
+                        if token.span.hi != pos || token.fragments.is_empty() {
+                            token.fragments.push(String::new());
+                        }
+                        token.fragments.last_mut().unwrap().push(c);
+                        token.pattern.push(c);
+                        token.span.hi = *lo + BytePos(raw_offset(i + c.len_utf8()) as u32);
+                    }
+                }
+                Segment::Hole(span) => {
+                    let token = current.get_or_insert_with(|| Token {
+                        span: *span,
+                        pattern: String::new(),
+                        fragments: vec![],
+                        has_hole: false,
+                    });
+                    token.pattern.push_str("${…}");
+                    token.span.hi = span.hi;
+                    token.has_hole = true;
+                }
+            }
+        }
+        tokens.extend(current);
+
+        for token in tokens {
+            if !token.has_hole || token.fragments.is_empty() {
+                continue;
+            }
+
+            let fragments: Vec<_> = token.fragments.iter().map(|f| format!("{f:?}")).collect();
+            self.pending_findings.push(PendingFinding {
+                kind: FindingKind::DynamicClass,
+                span: token.span,
+                message: format!(
+                    "class \"{}\" is built dynamically and can't be prefixed, from the fragments {}",
+                    token.pattern,
+                    fragments.join(", ")
+                ),
+            });
+        }
+    }
+
+    /// Visits the arguments of a call to a function, or method, that goes by any of the `names`.
+    /// `chain` is the member chain of the callee, e.g. `["el", "classList", "add"]`.
+    fn visit_call_args(&mut self, chain: &[String], args: &mut Vec<ExprOrSpread>) {
+        let names = fn_call_names(chain);
+        if names
+            .iter()
+            .any(|name| self.is_excluded(name, ScopeVariant::FnCall))
+        {
+            self.visit_out_of_scope(args);
+            return;
+        }
+
+        let selectors = chain_suffixes(chain)
+            .iter()
+            .find_map(|name| self.starts_a_valid_scope(name, ScopeVariant::Selectors));
+
+        if let Some(scope) = selectors {
+            self.visit_selectors_in_scope(scope, args);
+            return;
+        }
+
+        let values: Vec<&ScopeValue> = self
+            .scopes
+            .iter()
+            .filter(|scope| !scope.is_exclusion())
+            .flat_map(|scope| scope.values_for(ScopeVariant::FnCall))
+            .filter(|value| names.iter().any(|name| value.matches(name)))
+            .collect();
+
+        if values.is_empty() {
+            args.visit_mut_with(self);
+            return;
+        }
+
+        if let Some(value) = values.iter().find(|v| v.selector().is_none()) {
+            self.visit_in_scope(ScopeEntry::new(ScopeVariant::FnCall, value), args);
+            return;
+        }
+
+        for (i, arg) in args.iter_mut().enumerate() {
+            let selected: Vec<_> = values
+                .iter()
+                .filter_map(|v| {
+                    let selector = v.selector().filter(|s| s.index == i)?;
+                    Some((ScopeEntry::new(ScopeVariant::FnCall, v), &*selector.path))
+                })
+                .collect();
+
+            if selected.is_empty() || arg.spread.is_some() {
+                arg.visit_mut_with(self);
+            } else {
+                self.visit_selected(&mut arg.expr, selected);
+            }
+        }
+    }
+
+    /// Visits the parts of `expr` at the end of any of the `selected` paths in scope,
+    /// and the rest of it as usual.
+    fn visit_selected(
+        &mut self,
+        expr: &mut Expr,
+        selected: Vec<(ScopeEntry, &'scopes [PathSegment])>,
+    ) {
+        if let Some((scope, _)) = selected.iter().find(|(_, path)| path.is_empty()) {
+            self.visit_in_scope(scope.clone(), expr);
+            return;
+        }
+
+        match expr {
+            Expr::Paren(e) => self.visit_selected(&mut e.expr, selected),
+            Expr::TsConstAssertion(e) => self.visit_selected(&mut e.expr, selected),
+            Expr::TsAs(e) => self.visit_selected(&mut e.expr, selected),
+            Expr::TsSatisfies(e) => self.visit_selected(&mut e.expr, selected),
+            Expr::Object(object) => {
+                for prop in &mut object.props {
+                    if let PropOrSpread::Prop(prop) = prop {
+                        if let Prop::KeyValue(kv) = prop.as_mut() {
+                            let next = prop_key(&kv.key)
+                                .map(|key| step(&selected, |segment| segment.matches_key(key)));
+
+                            if let Some(next) = next.filter(|next| !next.is_empty()) {
+                                kv.key.visit_mut_with(self);
+                                self.visit_selected(&mut kv.value, next);
+                                continue;
+                            }
+                        }
+                    }
+
+                    prop.visit_mut_with(self);
+                }
+            }
+            Expr::Array(array) => {
+                for (i, elem) in array.elems.iter_mut().enumerate() {
+                    if let Some(ExprOrSpread { spread: None, expr }) = elem {
+                        let next = step(&selected, |segment| segment.matches_index(i));
+                        if !next.is_empty() {
+                            self.visit_selected(expr, next);
+                            continue;
+                        }
+                    }
+
+                    elem.visit_mut_with(self);
+                }
+            }
+            _ => expr.visit_mut_with(self),
+        }
+    }
+
+    pub fn visit_in_scope<N: VisitMutWith<Self>>(&mut self, scope: ScopeEntry, n: &mut N) {
+        self.visit_with_scope(Some(scope), false, n);
+    }
+
+    fn visit_out_of_scope<N: VisitMutWith<Self>>(&mut self, n: &mut N) {
+        self.visit_with_scope(None, false, n);
+    }
+
+    /// Like [`Self::visit_in_scope`], but strings are css selectors rather than classes.
+    fn visit_selectors_in_scope<N: VisitMutWith<Self>>(&mut self, scope: ScopeEntry, n: &mut N) {
+        self.visit_with_scope(Some(scope), true, n);
+    }
+
+    /// Visits `n` in the given scope, or out of any, then goes back to the enclosing one.
+    fn visit_with_scope<N: VisitMutWith<Self>>(
+        &mut self,
+        scope: Option<ScopeEntry>,
+        is_in_selector: bool,
+        n: &mut N,
+    ) {
+        let is_in_scope = std::mem::replace(&mut self.is_in_scope, scope.is_some());
+        let active_scope = std::mem::replace(&mut self.active_scope, scope);
+        let was_in_selector = std::mem::replace(&mut self.is_in_selector, is_in_selector);
+        if let Some(explain) = &mut self.explain {
+            explain.enter(self.active_scope.clone());
+        }
+
+        n.visit_mut_with(self);
+
+        if let Some(explain) = &mut self.explain {
+            explain.exit();
+        }
+
+        self.is_in_scope = is_in_scope;
+        self.active_scope = active_scope;
+        self.is_in_selector = was_in_selector;
+    }
+
+    /// A binding referenced where a comment directive leaves classes alone counts as used
+    /// out of scope, so it's not followed.
+    fn reference(&mut self, ident: &swc_ecma_ast::Ident) {
+        let scope = self
+            .active_scope
+            .as_ref()
+            .filter(|_| self.is_in_scope && !self.directives.is_disabled(ident.span.lo));
+        self.bindings.reference(&ident.sym, scope);
+    }
+
+    /// Records how the tokens of a string literal match the css input, see [`Explain`].
+    fn explain_str(&mut self, n: &swc_ecma_ast::Str) {
+        if self.explain.is_none() {
+            return;
+        }
+
+        let kind = match self.is_in_selector {
+            true => LiteralKind::Selector,
+            false => LiteralKind::Str,
+        };
+
+        let tokens = tokens(&n.value)
+            .filter(|_| kind == LiteralKind::Str)
+            .map(|(_, token)| (token.to_string(), self.token_match(token)))
+            .collect();
+
+        let is_disabled = self.directives.is_disabled(n.span.lo);
+        let explain = self.explain.as_mut().expect("explain was checked above");
+        explain.literal(
+            n.span.lo,
+            kind,
+            &n.value,
+            self.is_in_scope,
+            is_disabled,
+            tokens,
+        );
+    }
+
+    /// How a token, without its variants, matches the css input.
+    fn token_match(&self, token: &str) -> TokenMatch {
+        let class = token.rsplit(':').next().unwrap_or(token);
+        let is_class = |c: &str| self.class_names.iter().any(|name| name == c);
+
+        if is_class(class) {
+            TokenMatch::Class
+        } else if !self.prefix.is_empty() && class.strip_prefix(self.prefix).is_some_and(is_class) {
+            TokenMatch::Prefixed
+        } else {
+            TokenMatch::Unknown
+        }
+    }
+
+    /// How the tokens of a template's text match the css input, for [`Explain`].
+    fn template_token_matches(&self, n: &swc_ecma_ast::Tpl) -> Vec<(String, TokenMatch)> {
+        let mut matches = vec![];
+        for (i, quasi) in n.quasis.iter().enumerate() {
+            let value = quasi.cooked.as_deref().unwrap_or_default();
+            for (offset, token) in tokens(value) {
+                let matched = match is_whole_in_quasi(i, n.exprs.len(), value, offset, token) {
+                    true => self.token_match(token),
+                    false => TokenMatch::Dynamic,
+                };
+                matches.push((token.to_string(), matched));
+            }
+        }
+        matches
+    }
+
+    /// Prefixes the classes in the text of a string, or of a template, that starts at
+    /// `content_start`. The tokens `is_whole` rejects, like `px-` in `` `px-${size}` ``, are
+    /// left alone.
+    fn prefix_text(
+        &mut self,
+        value: &str,
+        raw: &RawStr,
+        content_start: BytePos,
+        is_whole: impl Fn(usize, &str) -> bool,
+    ) {
+        // (byte offset into the string, class) of every class that got prefixed
+        let mut prefixed_classes = vec![];
+        // (byte offset into the string, class) of every class not in the css input
+        let mut unknown_classes = vec![];
+        for (class_offset, class) in tokens(value) {
+            if !is_whole(class_offset, class) {
+                continue;
+            }
+
+            let actual_class = class
+                .split(':')
+                .next_back()
+                .expect("class should not have been an empty string");
+            let actual_class_offset = class_offset + class.len() - actual_class.len();
+
+            if self.class_names.iter().any(|name| name == actual_class) {
+                prefixed_classes.push((actual_class_offset, actual_class.to_string()));
+                continue;
+            }
+
+            let is_prefixed_already = actual_class
+                .strip_prefix(self.prefix)
+                .is_some_and(|c| self.class_names.iter().any(|name| name == c));
+
+            if !is_prefixed_already && !actual_class.trim().is_empty() {
+                unknown_classes.push((actual_class_offset, actual_class.to_string()));
+            }
+        }
+
+        if let Some(lint) = &mut self.unknown_classes {
+            for (offset, class) in unknown_classes {
+                if lint.is_allowed(&class) {
+                    continue;
+                }
+
+                let message = match lint.suggest(&class, self.class_names) {
+                    Some(suggestion) => {
+                        format!("unknown class \"{class}\", did you mean \"{suggestion}\"?")
+                    }
+                    None => format!("unknown class \"{class}\""),
+                };
+
+                let lo = content_start + BytePos(raw.raw_offset(offset) as u32);
+                self.pending_findings.push(PendingFinding {
+                    kind: FindingKind::UnknownClass,
+                    span: Span::new(lo, lo + BytePos(class.len() as u32), Default::default()),
+                    message,
+                });
+            }
+        }
+
+        if !prefixed_classes.is_empty() {
+            self.replace_text(content_start, raw, prefixed_classes);
+        }
+    }
+
+    /// Prefixes the classes, of a string or template in scope, at the given offsets into its value.
+    /// It's rewritten from its raw text, so escapes are kept as written.
+    /// `prefixed_classes` are (byte offset into the value, class).
+    fn replace_text(
+        &mut self,
+        content_start: BytePos,
+        raw: &RawStr,
+        prefixed_classes: Vec<(usize, String)>,
+    ) {
+        let start = content_start.0 as usize - 1; // - 1 because swc bytepos is 1-based
+        let end = start + raw.raw.len() - 1;
+
+        let replacement = raw.insert_at(
+            prefixed_classes.iter().map(|(offset, _)| *offset),
+            self.prefix,
+        );
+
+        if self.check {
+            for (offset, class) in &prefixed_classes {
+                let lo = content_start + BytePos(raw.raw_offset(*offset) as u32);
+                self.pending_findings.push(PendingFinding {
+                    kind: FindingKind::LegacyClass,
+                    span: Span::new(lo, lo + BytePos(class.len() as u32), Default::default()),
+                    message: format!(
+                        "class \"{class}\" is missing the prefix \"{}\"",
+                        self.prefix
+                    ),
+                });
+            }
+        }
+
+        self.replacements.push(replacements::Replacement::new(
+            start..=end,
+            raw.raw.as_bytes(),
+            replacement.as_bytes(),
+        ));
+
+        self.pending.push(PendingReplacement {
+            // the opening quote, or backtick
+            pos: content_start - BytePos(1),
+            scope: self
+                .active_scope
+                .clone()
+                .expect("a scope should be active when in scope"),
+            original: raw.raw.to_string(),
+            new: replacement,
+            classes: prefixed_classes
+                .into_iter()
+                .map(|(_, class)| class)
+                .collect(),
+        });
+    }
+
+    /// Prefixes the class selectors, in a string that's a css selector, that match `class_names`.
+    /// e.g. `.card > .hidden` -> `.card > .tw-hidden`
+    fn prefix_selector_classes(&mut self, n: &swc_ecma_ast::Str, raw: &RawStr) {
+        let cm: Lrc<SourceMap> = Default::default();
+        let fm = cm.new_source_file(FileName::Anon, n.value.to_string());
+
+        let mut errors = vec![];
+        let Ok(selectors) = swc_css::parser::parse_file::<swc_css::ast::SelectorList>(
+            &fm,
+            None,
+            Default::default(),
+            &mut errors,
+        ) else {
+            return;
+        };
+
+        if !errors.is_empty() {
+            return;
+        }
+
+        // (span, value, raw) of every class selector
+        struct ClassSelectors(Vec<(Span, String, String)>);
+
+        impl swc_css::visit::Visit for ClassSelectors {
+            fn visit_class_selector(&mut self, n: &swc_css::ast::ClassSelector) {
+                let raw = n.text.raw.as_deref().unwrap_or(&n.text.value);
+                self.0
+                    .push((n.text.span, n.text.value.to_string(), raw.to_string()));
+            }
+        }
+
+        let mut classes = ClassSelectors(vec![]);
+        swc_css::visit::VisitWith::visit_with(&selectors, &mut classes);
+
+        // (byte offset into the string, class) of every class that got prefixed
+        let mut prefixed_classes = vec![];
+
+        for (span, value, raw) in classes.0 {
+            let class = value.split(':').next_back().unwrap_or_default();
+            // variants are escaped, e.g. `.md\:hidden`
+            let class_start = raw.rfind(':').map(|i| i + 1).unwrap_or(0);
+
+            if self.class_names.iter().any(|name| name == class) {
+                let offset = (span.lo - fm.start_pos).0 as usize + class_start;
+                prefixed_classes.push((offset, class.to_string()));
+            }
+        }
+
+        if prefixed_classes.is_empty() {
+            return;
+        }
+
+        self.replace_text(n.span.lo + BytePos(1), raw, prefixed_classes);
     }
 }
 
 impl<'s, 'cn, 'scopes> VisitMut for ApplyTailwindPrefix<'s, 'cn, 'scopes> {
+    fn visit_mut_jsx_opening_element(&mut self, n: &mut swc_ecma_ast::JSXOpeningElement) {
+        let name = jsx_element_name(&n.name);
+        let element = self.element.replace(name.clone());
+
+        n.name.visit_mut_with(self);
+        n.type_args.visit_mut_with(self);
+
+        let scope = Some(&name)
+            .filter(|name| !self.is_excluded(name, ScopeVariant::Elements))
+            .and_then(|name| self.starts_a_valid_scope(name, ScopeVariant::Elements));
+
+        match scope {
+            Some(scope) => {
+                for attr in &mut n.attrs {
+                    if has_string_value(attr) {
+                        self.visit_in_scope(scope.clone(), attr);
+                    } else {
+                        attr.visit_mut_with(self);
+                    }
+                }
+            }
+            None => n.attrs.visit_mut_with(self),
+        }
+
+        self.element = element;
+    }
+
     fn visit_mut_jsx_attr(&mut self, n: &mut swc_ecma_ast::JSXAttr) {
         if let JSXAttrName::Ident(name) = &n.name {
-            if self.starts_a_valid_scope(name, ScopeVariant::AttrNames) {
-                self.is_in_scope = true;
-                n.value.visit_mut_with(self);
-                self.is_in_scope = false;
+            if self.is_excluded(&name.sym, ScopeVariant::AttrNames) {
+                self.visit_out_of_scope(&mut n.value);
+                return;
+            }
+
+            if let Some(scope) = self.starts_a_valid_scope(&name.sym, ScopeVariant::AttrNames) {
+                self.visit_in_scope(scope, &mut n.value);
+                return;
             }
         }
 
@@ -152,86 +823,593 @@ impl<'s, 'cn, 'scopes> VisitMut for ApplyTailwindPrefix<'s, 'cn, 'scopes> {
     }
 
     fn visit_mut_call_expr(&mut self, n: &mut swc_ecma_ast::CallExpr) {
-        if let Callee::Expr(expr) = &n.callee {
-            if let Expr::Ident(name) = expr.as_ref() {
-                if self.starts_a_valid_scope(name, ScopeVariant::FnCall) {
-                    self.is_in_scope = true;
-                    n.args.visit_mut_with(self);
-                    self.is_in_scope = false;
-                }
+        let chain = match &n.callee {
+            Callee::Expr(expr) => owned(member_chain(expr)),
+            _ => vec![],
+        };
+
+        n.callee.visit_mut_with(self);
+        n.type_args.visit_mut_with(self);
+        self.visit_call_args(&chain, &mut n.args);
+    }
+
+    fn visit_mut_opt_call(&mut self, n: &mut swc_ecma_ast::OptCall) {
+        let chain = owned(member_chain(&n.callee));
+
+        n.callee.visit_mut_with(self);
+        n.type_args.visit_mut_with(self);
+        self.visit_call_args(&chain, &mut n.args);
+    }
+
+    fn visit_mut_assign_expr(&mut self, n: &mut swc_ecma_ast::AssignExpr) {
+        let names = match &n.left {
+            AssignTarget::Simple(SimpleAssignTarget::Member(member)) => {
+                chain_suffixes(&owned(member_expr_chain(member)))
             }
+            _ => vec![],
+        };
+
+        if names
+            .iter()
+            .any(|name| self.is_excluded(name, ScopeVariant::Assignments))
+        {
+            n.left.visit_mut_with(self);
+            self.visit_out_of_scope(&mut n.right);
+            return;
+        }
+
+        let scope = names
+            .iter()
+            .find_map(|name| self.starts_a_valid_scope(name, ScopeVariant::Assignments));
+
+        if let Some(scope) = scope {
+            n.left.visit_mut_with(self);
+            self.visit_in_scope(scope, &mut n.right);
+            return;
+        }
+
+        n.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_ts_type_alias_decl(&mut self, n: &mut swc_ecma_ast::TsTypeAliasDecl) {
+        if let Some(scope) = self.starts_a_valid_scope(&n.id.sym, ScopeVariant::Types) {
+            n.id.visit_mut_with(self);
+            n.type_params.visit_mut_with(self);
+            self.visit_in_scope(scope, &mut n.type_ann);
+            return;
+        }
+
+        n.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_ts_enum_decl(&mut self, n: &mut swc_ecma_ast::TsEnumDecl) {
+        if let Some(scope) = self.starts_a_valid_scope(&n.id.sym, ScopeVariant::Enums) {
+            n.id.visit_mut_with(self);
+            for member in &mut n.members {
+                member.id.visit_mut_with(self);
+                self.visit_in_scope(scope.clone(), &mut member.init);
+            }
+            return;
         }
 
         n.visit_mut_children_with(self);
     }
 
     fn visit_mut_key_value_prop(&mut self, n: &mut swc_ecma_ast::KeyValueProp) {
-        if let PropName::Ident(ident) = &n.key {
-            if self.starts_a_valid_scope(ident, ScopeVariant::RecordEntries) {
-                self.is_in_scope = true;
-                n.value.visit_mut_with(self);
-                self.is_in_scope = false;
+        if let Some(key) = prop_key(&n.key) {
+            if self.is_excluded(key, ScopeVariant::RecordEntries) {
+                self.visit_out_of_scope(&mut n.value);
+                return;
+            }
+
+            if let Some(scope) = self.starts_a_valid_scope(key, ScopeVariant::RecordEntries) {
+                self.visit_in_scope(scope, &mut n.value);
+                return;
             }
         }
 
         n.visit_mut_children_with(self);
     }
 
-    fn visit_mut_str(&mut self, n: &mut swc_ecma_ast::Str) {
-        if !self.is_in_scope {
+    fn visit_mut_var_decl(&mut self, n: &mut swc_ecma_ast::VarDecl) {
+        for decl in &n.decls {
+            self.bindings.declare_const(decl, n.kind);
+        }
+
+        n.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_var_declarator(&mut self, n: &mut swc_ecma_ast::VarDeclarator) {
+        // e.g. `const gap: Gap = "gap-2"`, where `Gap` is in a type scope
+        let type_name = match &n.name {
+            Pat::Ident(binding) => {
+                binding
+                    .type_ann
+                    .as_deref()
+                    .and_then(|ann| match ann.type_ann.as_ref() {
+                        TsType::TsTypeRef(TsTypeRef {
+                            type_name: TsEntityName::Ident(name),
+                            ..
+                        }) => Some(name.sym.as_str()),
+                        _ => None,
+                    })
+            }
+            _ => None,
+        };
+
+        if let Some(scope) =
+            type_name.and_then(|t| self.starts_a_valid_scope(t, ScopeVariant::Types))
+        {
+            n.name.visit_mut_with(self);
+            self.visit_in_scope(scope, &mut n.init);
             return;
         }
 
-        let mut has_prefixed_some = false;
-        let replacements: Vec<_> = n
-            .value
-            .split(' ')
-            .map(|class| {
-                if class.is_empty() {
-                    return class.to_string();
+        // e.g. `const buttonClasses = "px-2"`, where `buttonClasses` is in a var scope
+        if let Pat::Ident(binding) = &n.name {
+            if self.is_excluded(&binding.id.sym, ScopeVariant::Variables) {
+                n.name.visit_mut_with(self);
+                self.visit_out_of_scope(&mut n.init);
+                return;
+            }
+
+            if let Some(scope) = self.starts_a_valid_scope(&binding.id.sym, ScopeVariant::Variables)
+            {
+                n.name.visit_mut_with(self);
+                self.visit_in_scope(scope, &mut n.init);
+                return;
+            }
+        }
+
+        n.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_import_decl(&mut self, n: &mut swc_ecma_ast::ImportDecl) {
+        if !n.type_only {
+            for specifier in &n.specifiers {
+                match specifier {
+                    ImportSpecifier::Named(named) => {
+                        let imported = match &named.imported {
+                            Some(ModuleExportName::Ident(i)) => i.sym.as_str(),
+                            Some(ModuleExportName::Str(s)) => s.value.as_str(),
+                            None => named.local.sym.as_str(),
+                        };
+                        self.bindings
+                            .import(&named.local.sym, &n.src.value, imported);
+                    }
+                    ImportSpecifier::Default(default) => {
+                        self.bindings
+                            .import(&default.local.sym, &n.src.value, "default");
+                    }
+                    ImportSpecifier::Namespace(_) => {}
                 }
+            }
+        }
 
-                let mut class_fragments: Vec<_> = class.split(':').collect();
-                let actual_class = class_fragments
-                    .last_mut()
-                    .expect("class should not have been an empty string");
+        n.visit_mut_children_with(self);
+    }
 
-                if self.class_names.iter().any(|name| name == *actual_class) {
-                    let prefixed = format!("{}{}", self.prefix, actual_class);
-                    *actual_class = prefixed.as_str();
-                    has_prefixed_some = true;
-                    return class_fragments.join(":");
+    fn visit_mut_export_decl(&mut self, n: &mut swc_ecma_ast::ExportDecl) {
+        if let Decl::Var(var) = &n.decl {
+            for decl in &var.decls {
+                if let Pat::Ident(binding) = &decl.name {
+                    self.bindings.export(&binding.id.sym, &binding.id.sym);
                 }
+            }
+        }
 
-                class.to_string()
-            })
-            .collect();
+        n.visit_mut_children_with(self);
+    }
 
-        if has_prefixed_some {
-            let start = n.span.lo.0 as usize - 1; // - 1 because swc bytepos is 1-based
-            let end = n.span.hi.0 as usize - 1;
+    fn visit_mut_named_export(&mut self, n: &mut swc_ecma_ast::NamedExport) {
+        if n.src.is_none() {
+            for specifier in &n.specifiers {
+                if let swc_ecma_ast::ExportSpecifier::Named(named) = specifier {
+                    let ModuleExportName::Ident(local) = &named.orig else {
+                        continue;
+                    };
+                    let exported = match &named.exported {
+                        Some(ModuleExportName::Ident(i)) => i.sym.as_str(),
+                        Some(ModuleExportName::Str(s)) => s.value.as_str(),
+                        None => local.sym.as_str(),
+                    };
+                    self.bindings.export(exported, &local.sym);
+                }
+            }
+        }
 
-            // exclude the begining and end quotes counted in the span
-            let start = start + 1;
-            let end = end - 2;
+        n.visit_mut_children_with(self);
+    }
 
-            debug_assert_eq!(
-                end - start + 1, // computed value length
-                n.value.as_bytes().len()
+    fn visit_mut_export_default_expr(&mut self, n: &mut swc_ecma_ast::ExportDefaultExpr) {
+        if let Expr::Ident(local) = n.expr.as_ref() {
+            // exporting isn't a use of the binding outside of a scope
+            self.bindings.export("default", &local.sym);
+            return;
+        }
+
+        n.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_binding_ident(&mut self, n: &mut swc_ecma_ast::BindingIdent) {
+        self.bindings.declare(&n.id.sym);
+
+        n.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_expr(&mut self, n: &mut Expr) {
+        if let Expr::Ident(ident) = n {
+            self.reference(ident);
+        }
+
+        n.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_prop(&mut self, n: &mut swc_ecma_ast::Prop) {
+        if let swc_ecma_ast::Prop::Shorthand(ident) = n {
+            self.reference(ident);
+        }
+
+        n.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_tpl(&mut self, n: &mut swc_ecma_ast::Tpl) {
+        let is_disabled = self.directives.is_disabled(n.span.lo);
+
+        let matches = self
+            .explain
+            .is_some()
+            .then(|| self.template_token_matches(n));
+        if let (Some(explain), Some(matches)) = (&mut self.explain, matches) {
+            let text: Vec<_> = n.quasis.iter().map(|quasi| &*quasi.raw).collect();
+            explain.literal(
+                n.span.lo,
+                LiteralKind::Template,
+                &text.join("${…}"),
+                self.is_in_scope,
+                is_disabled,
+                matches,
             );
+        }
+
+        if !self.is_in_scope || self.is_in_selector || is_disabled {
+            n.visit_mut_children_with(self);
+            return;
+        }
+
+        for (i, quasi) in n.quasis.iter().enumerate() {
+            let Some((value, raw)) = raw_quasi(quasi) else {
+                continue;
+            };
+            debug_assert_eq!((quasi.span.hi - quasi.span.lo).0 as usize, raw.raw.len());
+
+            let holes = n.exprs.len();
+            self.prefix_text(value, &raw, quasi.span.lo, |offset, token| {
+                is_whole_in_quasi(i, holes, value, offset, token)
+            });
+        }
+
+        if !n.exprs.is_empty() {
+            let mut segments = vec![];
+            for (i, quasi) in n.quasis.iter().enumerate() {
+                segments.push(match raw_quasi(quasi) {
+                    Some((text, raw)) => Segment::Text {
+                        lo: quasi.span.lo,
+                        text,
+                        raw: Some(raw),
+                    },
+                    None => Segment::Text {
+                        lo: quasi.span.lo,
+                        text: &quasi.raw,
+                        raw: None,
+                    },
+                });
+                if let Some(expr) = n.exprs.get(i) {
+                    segments.push(Segment::Hole(expr.span()));
+                }
+            }
+            self.report_dynamic_classes(&segments);
+        }
+
+        n.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_bin_expr(&mut self, n: &mut swc_ecma_ast::BinExpr) {
+        if !self.is_in_scope
+            || self.is_in_selector
+            || self.directives.is_disabled(n.span.lo)
+            || n.op != BinaryOp::Add
+        {
+            n.visit_mut_children_with(self);
+            return;
+        }
+
+        fn flatten<'e>(expr: &'e mut Expr, operands: &mut Vec<&'e mut Expr>) {
+            let is_add = matches!(expr, Expr::Bin(b) if b.op == BinaryOp::Add);
+            if !is_add && !matches!(expr, Expr::Paren(_)) {
+                operands.push(expr);
+                return;
+            }
+
+            match expr {
+                Expr::Bin(b) => {
+                    flatten(&mut b.left, operands);
+                    flatten(&mut b.right, operands);
+                }
+                Expr::Paren(p) => flatten(&mut p.expr, operands),
+                _ => unreachable!("only additions and parentheses are flattened"),
+            }
+        }
+
+        let mut operands = vec![];
+        flatten(&mut n.left, &mut operands);
+        flatten(&mut n.right, &mut operands);
+
+        let is_string_concatenation = operands.iter().any(|e| matches!(e, Expr::Lit(Lit::Str(_))));
+
+        if is_string_concatenation {
+            let segments: Vec<_> = operands
+                .iter()
+                .map(|e| match e {
+                    // + 1 to skip the opening quote
+                    Expr::Lit(Lit::Str(s)) => Segment::Text {
+                        lo: s.span.lo + BytePos(1),
+                        text: &s.value,
+                        raw: raw_str(s),
+                    },
+                    e => Segment::Hole(e.span()),
+                })
+                .collect();
+            self.report_dynamic_classes(&segments);
+        }
+
+        // the operands, not the `+` expressions they're in, which are part of this concatenation;
+        // a concatenation nested in an operand, like in `"a-" + cn("bg-" + x)`, is its own
+        for operand in operands {
+            operand.visit_mut_with(self);
+        }
+    }
+
+    fn visit_mut_str(&mut self, n: &mut swc_ecma_ast::Str) {
+        if !self.is_in_scope && self.directives.is_forced(n.span.lo) {
+            // e.g. `/* cnat */ "px-2"`
+            let scope = ScopeEntry {
+                variant: "comment".to_string(),
+                value: "cnat".to_string(),
+            };
+            self.visit_in_scope(scope, n);
+            return;
+        }
+
+        self.explain_str(n);
+
+        if !self.is_in_scope || self.directives.is_disabled(n.span.lo) {
+            return;
+        }
+
+        let Some(raw) = raw_str(n) else {
+            return;
+        };
+
+        if self.is_in_selector {
+            self.prefix_selector_classes(n, &raw);
+            return;
+        }
+
+        self.prefix_text(&n.value, &raw, n.span.lo + BytePos(1), |_, _| true);
+    }
+}
+
+/// The js/ts files in a directory, and its subdirectories, that aren't ignored.
+pub fn collect_files(path: &Path) -> Vec<PathBuf> {
+    let walk = ignore::WalkBuilder::new(path)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .build();
+
+    let mut files = vec![];
+    for r in walk {
+        match r {
+            Ok(entry) => {
+                let filepath = entry.path();
+                let is_supported_file = filepath.is_file()
+                    && filepath
+                        .extension()
+                        .map(|e| ["ts", "js", "jsx", "tsx"].map(OsStr::new).contains(&e))
+                        .unwrap_or(false);
+
+                if is_supported_file {
+                    files.push(filepath.to_path_buf());
+                }
+            }
+            Err(err) => eprintln!("[Error] {err:#}"),
+        };
+    }
+
+    files
+}
+
+pub fn parse(
+    source_file: &Path,
+    comments: Option<&dyn Comments>,
+) -> anyhow::Result<(Lrc<SourceMap>, Lrc<SourceFile>, Program)> {
+    let cm: Lrc<SourceMap> = Default::default();
+    let error_handler = Handler::with_tty_emitter(ColorConfig::Auto, true, false, Some(cm.clone()));
+
+    let fm = cm
+        .load_file(source_file)
+        .context("failed to load source file")?;
 
-            let replacement = replacements.join(" ");
+    let syntax = match source_file.extension().and_then(|e| e.to_str()) {
+        Some("js") | Some("jsx") => Syntax::Es(swc_ecma_parser::EsConfig {
+            jsx: true,
+            ..Default::default()
+        }),
+        Some("ts") => Syntax::Typescript(Default::default()),
+        Some("tsx") => Syntax::Typescript(swc_ecma_parser::TsConfig {
+            tsx: true,
+            ..Default::default()
+        }),
+        None => {
+            return Err(anyhow!(
+                "unknown filetype, missing extension: {}",
+                source_file.display()
+            ))
+        }
+        ext => return Err(anyhow!("unknown filetype: {ext:?}")),
+    };
+
+    let mut errors = vec![];
+    let program = parse_file_as_program(&fm, syntax, EsVersion::Es2015, comments, &mut errors)
+        .map_err(|e| e.into_diagnostic(&error_handler).emit())
+        .expect("failed to parse source code file");
+
+    Ok((cm, fm, program))
+}
+
+/// The paths, of the `selected` ones, that continue past a segment matching `matches`.
+fn step<'p>(
+    selected: &[(ScopeEntry, &'p [PathSegment])],
+    matches: impl Fn(&PathSegment) -> bool,
+) -> Vec<(ScopeEntry, &'p [PathSegment])> {
+    selected
+        .iter()
+        .filter(|(_, path)| matches(&path[0]))
+        .map(|(scope, path)| (scope.clone(), &path[1..]))
+        .collect()
+}
+
+/// The names in a member chain, as far back as they're known, e.g. `el.classList.add`
+/// or `classList.add` for `getElement().classList.add`.
+pub(crate) fn member_chain(expr: &Expr) -> Vec<&str> {
+    match expr {
+        Expr::Ident(ident) => vec![&ident.sym],
+        Expr::This(_) => vec!["this"],
+        Expr::Member(member) => member_expr_chain(member),
+        Expr::OptChain(chain) => match chain.base.as_ref() {
+            OptChainBase::Member(member) => member_expr_chain(member),
+            OptChainBase::Call(_) => vec![],
+        },
+        Expr::Paren(paren) => member_chain(&paren.expr),
+        _ => vec![],
+    }
+}
+
+fn member_expr_chain(member: &MemberExpr) -> Vec<&str> {
+    let prop: &str = match &member.prop {
+        MemberProp::Ident(ident) => &ident.sym,
+        MemberProp::Computed(computed) => match computed.expr.as_ref() {
+            Expr::Lit(Lit::Str(s)) => &s.value,
+            _ => return vec![],
+        },
+        MemberProp::PrivateName(_) => return vec![],
+    };
+
+    let mut chain = member_chain(&member.obj);
+    chain.push(prop);
+    chain
+}
+
+fn owned(chain: Vec<&str>) -> Vec<String> {
+    chain.into_iter().map(str::to_string).collect()
+}
 
-            self.replacements.push(replacements::Replacement::new(
-                start..=end,
-                n.value.as_bytes(),
-                replacement.as_bytes(),
-            ));
+/// The names a member chain can be matched by, e.g. `el.classList.add`, `classList.add`
+/// and `add`.
+fn chain_suffixes(chain: &[String]) -> Vec<String> {
+    (0..chain.len()).map(|i| chain[i..].join(".")).collect()
+}
+
+/// The names a call is matched by in `fn` scopes: the name of a bare function, or the names
+/// of a method with at least its object, e.g. `el.classList.add` and `classList.add`,
+/// so `fn:add` doesn't match `set.add(...)`.
+fn fn_call_names(chain: &[String]) -> Vec<String> {
+    let mut names = chain_suffixes(chain);
+    if chain.len() > 1 {
+        names.pop();
+    }
+    names
+}
+
+/// e.g. `Button`, `Dialog.Panel` or `svg:rect`
+pub(crate) fn jsx_element_name(name: &JSXElementName) -> String {
+    fn object_name(object: &JSXObject) -> String {
+        match object {
+            JSXObject::Ident(ident) => ident.sym.to_string(),
+            JSXObject::JSXMemberExpr(member) => {
+                format!("{}.{}", object_name(&member.obj), member.prop.sym)
+            }
         }
     }
+
+    match name {
+        JSXElementName::Ident(ident) => ident.sym.to_string(),
+        JSXElementName::JSXMemberExpr(member) => {
+            format!("{}.{}", object_name(&member.obj), member.prop.sym)
+        }
+        JSXElementName::JSXNamespacedName(name) => format!("{}:{}", name.ns.sym, name.name.sym),
+    }
+}
+
+/// Whether an attribute's value is a string, e.g. `color="red"` or `color={"red"}`.
+fn has_string_value(attr: &JSXAttrOrSpread) -> bool {
+    let JSXAttrOrSpread::JSXAttr(attr) = attr else {
+        return false;
+    };
+
+    match &attr.value {
+        Some(JSXAttrValue::Lit(_)) => true,
+        Some(JSXAttrValue::JSXExprContainer(container)) => matches!(
+            &container.expr,
+            JSXExpr::Expr(expr) if matches!(expr.as_ref(), Expr::Lit(Lit::Str(_)) | Expr::Tpl(_))
+        ),
+        _ => false,
+    }
+}
+
+/// The text of a string literal as written, between the quotes.
+fn raw_str(n: &swc_ecma_ast::Str) -> Option<RawStr<'_>> {
+    let raw = match n.raw.as_deref() {
+        Some(raw) => raw.get(1..raw.len().saturating_sub(1))?,
+        None => &n.value,
+    };
+
+    RawStr::new(raw, &n.value)
+}
+
+/// The value of a template's quasi, and its text as written. `None` for an invalid escape, which
+/// only tagged templates allow, or a line break the value doesn't map back to.
+fn raw_quasi(quasi: &swc_ecma_ast::TplElement) -> Option<(&str, RawStr<'_>)> {
+    let value = quasi.cooked.as_deref()?;
+    Some((value, RawStr::new(&quasi.raw, value)?))
+}
+
+/// Whether a token of the `i`th quasi of a template with `holes` expressions is a whole class,
+/// not a fragment joined to a `${…}`, like `px-` in `` `px-${size}` ``.
+fn is_whole_in_quasi(i: usize, holes: usize, value: &str, offset: usize, token: &str) -> bool {
+    let is_joined_before = i > 0 && offset == 0;
+    let is_joined_after = i < holes && offset + token.len() == value.len();
+    !is_joined_before && !is_joined_after
+}
+
+/// The name of a property key, if it's known statically, e.g. `className`, `"className"`,
+/// `["className"]` or `[`className`]`.
+pub(crate) fn prop_key(key: &PropName) -> Option<&str> {
+    match key {
+        PropName::Ident(ident) => Some(&ident.sym),
+        PropName::Str(s) => Some(&s.value),
+        PropName::Computed(computed) => match computed.expr.as_ref() {
+            Expr::Lit(Lit::Str(s)) => Some(&s.value),
+            Expr::Tpl(tpl) if tpl.exprs.is_empty() => tpl.quasis.first()?.cooked.as_deref(),
+            _ => None,
+        },
+        _ => None,
+    }
 }
 
 mod replacements {
+    use anyhow::anyhow;
+
+    const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
 
     pub struct Replacement {
         byte_range: std::ops::RangeInclusive<usize>,
@@ -248,6 +1426,10 @@ mod replacements {
             }
         }
 
+        pub fn start(&self) -> usize {
+            *self.byte_range.start()
+        }
+
         fn slide_span(&mut self, addition: usize) {
             let start = self.byte_range.start() + addition;
             let end = self.byte_range.end() + addition;
@@ -271,8 +1453,7 @@ mod replacements {
             let replace_with = self.new.iter().cloned();
             contents.splice(self.byte_range.clone(), replace_with);
 
-            let addition = self.new.len().saturating_sub(self.old.len());
-            return addition;
+            self.new.len().saturating_sub(self.old.len())
         }
 
         pub fn apply_all(rps: &mut [Replacement], mut contents: Vec<u8>) -> Vec<u8> {
@@ -280,7 +1461,31 @@ mod replacements {
             for rp in rps {
                 byte_additions += rp.apply(&mut contents, byte_additions);
             }
-            return contents;
+            contents
+        }
+
+        /// Applies the replacements to the exact bytes that were parsed, which swc loads without
+        /// a utf-8 bom. Fails if the file on disk is no longer what was parsed: the spans are stale.
+        pub fn apply_to_parsed(
+            rps: &mut [Replacement],
+            parsed: &[u8],
+            on_disk: &[u8],
+        ) -> anyhow::Result<Vec<u8>> {
+            let bom = if on_disk.starts_with(UTF8_BOM) {
+                UTF8_BOM
+            } else {
+                &[]
+            };
+
+            if on_disk[bom.len()..] != *parsed {
+                return Err(anyhow!(
+                    "the file changed since it was parsed, skipped it, run cnat again to transform it"
+                ));
+            }
+
+            let mut contents = bom.to_vec();
+            contents.extend(Replacement::apply_all(rps, parsed.to_vec()));
+            Ok(contents)
         }
     }
 
@@ -302,5 +1507,19 @@ mod replacements {
 
             assert_eq!(contents, "1abcdef5jklhelloworld".as_bytes());
         }
+
+        #[test]
+        fn replacements_to_parsed() {
+            let rps = || [Replacement::new(2..=3, "hi".as_bytes(), "hello".as_bytes())];
+
+            let contents = Replacement::apply_to_parsed(&mut rps(), b"a hi", b"a hi").unwrap();
+            assert_eq!(contents, "a hello".as_bytes());
+
+            let contents =
+                Replacement::apply_to_parsed(&mut rps(), b"a hi", b"\xEF\xBB\xBFa hi").unwrap();
+            assert_eq!(contents, "\u{feff}a hello".as_bytes());
+
+            assert!(Replacement::apply_to_parsed(&mut rps(), b"a hi", b"a hi!").is_err());
+        }
     }
 }